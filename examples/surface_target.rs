@@ -0,0 +1,34 @@
+// MIT/Apache2/ZLib License
+
+//! Bundle a window with its display, and pass it to something that creates a GPU surface.
+
+use owned_window_handle::raw_window_handle::{
+    DisplayHandle, HasDisplayHandle, HasWindowHandle, XlibDisplayHandle,
+};
+use owned_window_handle::{OwnedDisplayHandle, OwnedWindowDisplayHandle, OwnedWindowHandle};
+
+/// Stand-in for a surface constructor, like `wgpu::Instance::create_surface`.
+///
+/// The target is `'static`, so it has to own its window and display.
+fn create_surface(target: impl HasWindowHandle + HasDisplayHandle + 'static) {
+    let window = target.window_handle().unwrap();
+    let display = target.display_handle().unwrap();
+    println!(
+        "creating a surface for {:?} on {:?}",
+        window.as_raw(),
+        display.as_raw()
+    );
+}
+
+fn main() {
+    // SAFETY: This display is never connected to.
+    let display = unsafe { DisplayHandle::borrow_raw(XlibDisplayHandle::new(None, 0).into()) };
+    let display = OwnedDisplayHandle::new(display).unwrap();
+    let window = OwnedWindowHandle::from_xlib(1, 0);
+
+    let target = OwnedWindowDisplayHandle::new(window, display);
+
+    // Every surface gets its own clone of the window and the display.
+    create_surface(target.try_clone().unwrap());
+    create_surface(target);
+}
@@ -1,51 +1,84 @@
 // MIT/Apache2/ZLib License
 
 use winit::application::ApplicationHandler;
+use winit::event::WindowEvent;
 use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::window::{Window, WindowId};
 
 use owned_window_handle::OwnedWindowHandle;
 
 /// Run the application event loop.
 pub(crate) fn run(event_loop: EventLoop<()>) {
+    let mut app = Application::default();
+
     #[cfg(not(target_family = "wasm"))]
-    event_loop.run_app(&mut Application).unwrap();
+    event_loop.run_app(&mut app).unwrap();
 
     #[cfg(target_family = "wasm")]
-    winit::platform::web::EventLoopExtSys::spawn_app(event_loop, Application)
+    winit::platform::web::EventLoopExtSys::spawn_app(event_loop, app)
 }
 
 /// Application to run.
-struct Application;
+#[derive(Default)]
+struct Application {
+    /// The windows that were created.
+    windows: Vec<Window>,
+
+    /// Handles to the windows, as they'd be held by e.g. a renderer.
+    handles: Vec<OwnedWindowHandle>,
+}
+
+impl Application {
+    /// Find the index of the handle that a window event belongs to.
+    fn handle_for(&self, window_id: WindowId) -> Option<usize> {
+        let window = self
+            .windows
+            .iter()
+            .find(|window| window.id() == window_id)?;
+        self.handles
+            .iter()
+            .position(|handle| handle.matches_window(window).unwrap())
+    }
+}
 
 impl ApplicationHandler for Application {
     #[inline]
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        // Actual test: create a window.
-        let window = event_loop.create_window(Default::default()).unwrap();
-
-        // Create the window handle, which clones it.
-        let handle = OwnedWindowHandle::new(&window).unwrap();
-
-        // Create another.
-        let handle2 = OwnedWindowHandle::new(&window).unwrap();
+        // Actual test: create a couple of windows.
+        for _ in 0..2 {
+            let window = event_loop.create_window(Default::default()).unwrap();
 
-        // Drop the current window.
-        drop(window);
+            // Create the window handle, which clones it.
+            let handle = OwnedWindowHandle::new(&window).unwrap();
 
-        // Drop both handles.
-        drop((handle, handle2));
+            // The handle refers to the window it was created from.
+            assert!(handle.matches_window(&window).unwrap());
 
-        // Stop the loop now.
-        event_loop.exit();
+            window.request_redraw();
+            self.windows.push(window);
+            self.handles.push(handle);
+        }
     }
 
     #[inline]
     fn window_event(
         &mut self,
-        _event_loop: &ActiveEventLoop,
-        _window_id: winit::window::WindowId,
-        _event: winit::event::WindowEvent,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
     ) {
-        // Intentionally left blank.
+        if let WindowEvent::RedrawRequested = event {
+            // Route the event to the handle of the window it belongs to.
+            let handle = self.handles.remove(self.handle_for(window_id).unwrap());
+
+            // Drop the window, and then its handle.
+            self.windows.retain(|window| window.id() != window_id);
+            drop(handle);
+
+            // Stop the loop once every window is gone.
+            if self.windows.is_empty() {
+                event_loop.exit();
+            }
+        }
     }
 }
@@ -0,0 +1,97 @@
+/*
+ * Copyright (c) 2025 The Winit Publishers
+ *
+ * This software is release under one of the following licenses, at your option:
+ *
+ * - The MIT License
+ * - The Zlib License
+ * - The Apache License, Version 2.0
+ */
+
+/*
+ * C API for owned-window-handle.
+ *
+ * Requires the crate to be built with the `capi` feature, as a static or shared library:
+ *
+ *     cargo rustc --release --features capi --crate-type staticlib
+ *     cargo rustc --release --features capi --crate-type cdylib
+ *
+ * Every function returns one of the OWH_STATUS_* codes. Rust panics never unwind into
+ * the caller, they are reported as OWH_STATUS_PANIC instead.
+ */
+
+#ifndef OWNED_WINDOW_HANDLE_H
+#define OWNED_WINDOW_HANDLE_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes. */
+#define OWH_STATUS_OK 0
+#define OWH_STATUS_UNSUPPORTED 1
+#define OWH_STATUS_UNAVAILABLE 2
+#define OWH_STATUS_PLATFORM_MISMATCH 3
+#define OWH_STATUS_INVALID_ARGUMENT 4
+#define OWH_STATUS_ERROR 5
+#define OWH_STATUS_PANIC 6
+
+/* Window handle kinds. */
+#define OWH_KIND_XLIB 1
+#define OWH_KIND_XCB 2
+#define OWH_KIND_WAYLAND 3
+#define OWH_KIND_DRM 4
+#define OWH_KIND_GBM 5
+#define OWH_KIND_WIN32 6
+#define OWH_KIND_WINRT 7
+#define OWH_KIND_APPKIT 8
+#define OWH_KIND_UIKIT 9
+#define OWH_KIND_ANDROID_NDK 10
+#define OWH_KIND_OHOS_NDK 11
+#define OWH_KIND_HAIKU 12
+#define OWH_KIND_ORBITAL 13
+#define OWH_KIND_WEB 14
+#define OWH_KIND_WEB_CANVAS 15
+#define OWH_KIND_WEB_OFFSCREEN_CANVAS 16
+
+/* An owned window handle. */
+typedef struct OwnedWindowHandle OwnedWindowHandle;
+
+/* A raw window handle. */
+typedef struct OwhRawWindowHandle {
+    /* One of the OWH_KIND_* constants. */
+    uint32_t kind;
+
+    /*
+     * The window ID for Xlib and XCB, the HWND for Win32, the plane for DRM,
+     * the canvas ID for the web, the wasm-bindgen object for web canvases and a
+     * pointer for everything else.
+     *
+     * Xlib window ID's are an unsigned long, which is narrower than a pointer on
+     * 64-bit Windows. Xlib handles that don't fit are rejected there.
+     */
+    uintptr_t window;
+
+    /* The visual ID for Xlib and XCB, the HINSTANCE for Win32, zero otherwise. */
+    uintptr_t extra;
+} OwhRawWindowHandle;
+
+/* Create a new owned window handle. Free it with owh_window_handle_free. */
+int32_t owh_window_handle_new(const OwhRawWindowHandle *raw, OwnedWindowHandle **out);
+
+/* Clone an owned window handle. Free it with owh_window_handle_free. */
+int32_t owh_window_handle_clone(const OwnedWindowHandle *handle, OwnedWindowHandle **out);
+
+/* Free an owned window handle. Passing NULL does nothing. */
+int32_t owh_window_handle_free(OwnedWindowHandle *handle);
+
+/* Get the raw window handle, valid for as long as the owned handle is alive. */
+int32_t owh_window_handle_raw(const OwnedWindowHandle *handle, OwhRawWindowHandle *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* OWNED_WINDOW_HANDLE_H */
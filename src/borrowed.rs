@@ -0,0 +1,45 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Borrowed window handles.
+
+use core::fmt;
+use raw_window_handle::{HandleError, HasWindowHandle, WindowHandle};
+
+/// A window handle borrowed from something that implements [`HasWindowHandle`].
+///
+/// This is created using [`OwnedWindowHandle::borrowed`](crate::OwnedWindowHandle::borrowed).
+/// It never acquires or releases a reference to the window, and instead relies on its source
+/// outliving it.
+#[derive(Clone, Copy)]
+pub struct BorrowedWindowHandle<'a> {
+    /// The source of the window handle.
+    source: &'a dyn HasWindowHandle,
+}
+
+impl fmt::Debug for BorrowedWindowHandle<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BorrowedWindowHandle")
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> BorrowedWindowHandle<'a> {
+    /// Borrow a window handle from `source`.
+    pub(crate) fn new(source: &'a dyn HasWindowHandle) -> Self {
+        Self { source }
+    }
+}
+
+impl HasWindowHandle for BorrowedWindowHandle<'_> {
+    #[inline]
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        self.source.window_handle()
+    }
+}
@@ -0,0 +1,392 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! C API for owning window handles.
+//!
+//! This exposes [`OwnedWindowHandle`] to non-Rust code as an opaque pointer. The matching C
+//! header can be found at `include/owned_window_handle.h`.
+//!
+//! Every function returns one of the `OWH_STATUS_*` codes. Panics never unwind into the
+//! caller, they are caught and reported as [`OWH_STATUS_PANIC`] instead.
+//!
+//! The crate is built as an ordinary Rust library by default. To get a library that can be
+//! linked into a C or C++ program, pick the crate type on the command line:
+//!
+//! ```sh
+//! cargo rustc --release --features capi --crate-type staticlib
+//! ```
+//!
+//! Use `--crate-type cdylib` instead to build a shared library.
+
+use crate::{ErrorKind, OwnedWindowHandle};
+
+use core::ffi::{c_ulong, c_void};
+use core::num::{NonZeroIsize, NonZeroU32};
+use core::ptr::NonNull;
+use raw_window_handle::{
+    AndroidNdkWindowHandle, AppKitWindowHandle, DrmWindowHandle, GbmWindowHandle,
+    HaikuWindowHandle, OhosNdkWindowHandle, OrbitalWindowHandle, RawWindowHandle,
+    UiKitWindowHandle, WaylandWindowHandle, WebCanvasWindowHandle, WebOffscreenCanvasWindowHandle,
+    WebWindowHandle, Win32WindowHandle, WinRtWindowHandle, WindowHandle, XcbWindowHandle,
+    XlibWindowHandle,
+};
+use std::panic::{self, AssertUnwindSafe};
+
+/// The operation succeeded.
+pub const OWH_STATUS_OK: i32 = 0;
+/// The window handle is not supported.
+pub const OWH_STATUS_UNSUPPORTED: i32 = 1;
+/// The window handle is currently unavailable.
+pub const OWH_STATUS_UNAVAILABLE: i32 = 2;
+/// The window handle is for a different platform.
+pub const OWH_STATUS_PLATFORM_MISMATCH: i32 = 3;
+/// An argument was null or malformed.
+pub const OWH_STATUS_INVALID_ARGUMENT: i32 = 4;
+/// Some other error occurred.
+pub const OWH_STATUS_ERROR: i32 = 5;
+/// The library panicked. The state of the handles involved is unspecified.
+pub const OWH_STATUS_PANIC: i32 = 6;
+
+/// An Xlib window.
+pub const OWH_KIND_XLIB: u32 = 1;
+/// An XCB window.
+pub const OWH_KIND_XCB: u32 = 2;
+/// A Wayland surface.
+pub const OWH_KIND_WAYLAND: u32 = 3;
+/// A DRM plane.
+pub const OWH_KIND_DRM: u32 = 4;
+/// A GBM surface.
+pub const OWH_KIND_GBM: u32 = 5;
+/// A Win32 window.
+pub const OWH_KIND_WIN32: u32 = 6;
+/// A WinRT core window.
+pub const OWH_KIND_WINRT: u32 = 7;
+/// An AppKit view.
+pub const OWH_KIND_APPKIT: u32 = 8;
+/// A UIKit view.
+pub const OWH_KIND_UIKIT: u32 = 9;
+/// An Android native window.
+pub const OWH_KIND_ANDROID_NDK: u32 = 10;
+/// An OpenHarmony native window.
+pub const OWH_KIND_OHOS_NDK: u32 = 11;
+/// A Haiku window.
+pub const OWH_KIND_HAIKU: u32 = 12;
+/// An Orbital window.
+pub const OWH_KIND_ORBITAL: u32 = 13;
+/// A web canvas, identified by its `data-raw-handle` attribute.
+pub const OWH_KIND_WEB: u32 = 14;
+/// A web canvas element, identified by its `wasm-bindgen` object.
+pub const OWH_KIND_WEB_CANVAS: u32 = 15;
+/// A web offscreen canvas, identified by its `wasm-bindgen` object.
+pub const OWH_KIND_WEB_OFFSCREEN_CANVAS: u32 = 16;
+
+/// C representation of a raw window handle.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct OwhRawWindowHandle {
+    /// The kind of window handle, one of the `OWH_KIND_*` constants.
+    pub kind: u32,
+
+    /// The window.
+    ///
+    /// This is the window ID for Xlib and XCB, the `HWND` for Win32, the plane for DRM,
+    /// the canvas ID for the web, the `wasm-bindgen` object for web canvases and a pointer
+    /// for everything else.
+    ///
+    /// Xlib window ID's are an `unsigned long`, which is narrower than a pointer on 64-bit
+    /// Windows. Xlib handles whose window or visual ID don't fit are rejected with
+    /// [`OWH_STATUS_INVALID_ARGUMENT`] there.
+    pub window: usize,
+
+    /// Extra data for the window.
+    ///
+    /// This is the visual ID for Xlib and XCB, and the `HINSTANCE` for Win32. It is zero
+    /// for everything else.
+    pub extra: usize,
+}
+
+/// Create a new owned window handle.
+///
+/// On success, the new handle is written into `out`. It must be freed using
+/// [`owh_window_handle_free`].
+///
+/// # Safety
+///
+/// `raw` must point to a valid [`OwhRawWindowHandle`] describing a valid window, and `out`
+/// must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn owh_window_handle_new(
+    raw: *const OwhRawWindowHandle,
+    out: *mut *mut OwnedWindowHandle,
+) -> i32 {
+    guard(|| {
+        // SAFETY: The caller guarantees that `raw` is valid for reads.
+        let raw = match unsafe { raw.as_ref() }.and_then(from_c) {
+            Some(raw) => raw,
+            None => return OWH_STATUS_INVALID_ARGUMENT,
+        };
+        if out.is_null() {
+            return OWH_STATUS_INVALID_ARGUMENT;
+        }
+
+        // SAFETY: The caller guarantees that the window is valid.
+        let handle = unsafe { WindowHandle::borrow_raw(raw) };
+
+        match OwnedWindowHandle::from_window_handle(handle) {
+            Ok(handle) => {
+                // SAFETY: The caller guarantees that `out` is valid for writes.
+                unsafe { out.write(Box::into_raw(Box::new(handle))) };
+                OWH_STATUS_OK
+            }
+            Err(err) => status(err.kind()),
+        }
+    })
+}
+
+/// Clone an owned window handle.
+///
+/// On success, the new handle is written into `out`. It must be freed using
+/// [`owh_window_handle_free`].
+///
+/// # Safety
+///
+/// `handle` must be a handle created by this library that hasn't been freed yet, and `out`
+/// must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn owh_window_handle_clone(
+    handle: *const OwnedWindowHandle,
+    out: *mut *mut OwnedWindowHandle,
+) -> i32 {
+    guard(|| {
+        // SAFETY: The caller guarantees that `handle` is valid.
+        let handle = match unsafe { handle.as_ref() } {
+            Some(handle) => handle,
+            None => return OWH_STATUS_INVALID_ARGUMENT,
+        };
+        if out.is_null() {
+            return OWH_STATUS_INVALID_ARGUMENT;
+        }
+
+        match handle.try_clone() {
+            Ok(handle) => {
+                // SAFETY: The caller guarantees that `out` is valid for writes.
+                unsafe { out.write(Box::into_raw(Box::new(handle))) };
+                OWH_STATUS_OK
+            }
+            Err(err) => status(err.kind()),
+        }
+    })
+}
+
+/// Free an owned window handle, releasing its reference to the window.
+///
+/// Passing a null pointer does nothing. The handle is freed even if releasing the window
+/// fails.
+///
+/// # Safety
+///
+/// `handle` must be null or a handle created by this library that hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn owh_window_handle_free(handle: *mut OwnedWindowHandle) -> i32 {
+    guard(|| {
+        if !handle.is_null() {
+            // SAFETY: The caller guarantees that `handle` came from `Box::into_raw`.
+            drop(unsafe { Box::from_raw(handle) });
+        }
+
+        OWH_STATUS_OK
+    })
+}
+
+/// Get the raw window handle of an owned window handle.
+///
+/// On success, the raw handle is written into `out`. It is valid for as long as `handle` is
+/// not freed.
+///
+/// # Safety
+///
+/// `handle` must be a handle created by this library that hasn't been freed yet, and `out`
+/// must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn owh_window_handle_raw(
+    handle: *const OwnedWindowHandle,
+    out: *mut OwhRawWindowHandle,
+) -> i32 {
+    guard(|| {
+        // SAFETY: The caller guarantees that `handle` is valid.
+        let handle = match unsafe { handle.as_ref() } {
+            Some(handle) => handle,
+            None => return OWH_STATUS_INVALID_ARGUMENT,
+        };
+        if out.is_null() {
+            return OWH_STATUS_INVALID_ARGUMENT;
+        }
+
+        match to_c(handle.as_raw()) {
+            Some(raw) => {
+                // SAFETY: The caller guarantees that `out` is valid for writes.
+                unsafe { out.write(raw) };
+                OWH_STATUS_OK
+            }
+            None => OWH_STATUS_UNSUPPORTED,
+        }
+    })
+}
+
+/// Run `f`, catching any panic so it doesn't unwind across the FFI boundary.
+///
+/// Dropping a handle panics in debug builds if the window fails to be released, so even
+/// freeing a handle can panic.
+fn guard(f: impl FnOnce() -> i32) -> i32 {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(OWH_STATUS_PANIC)
+}
+
+/// Convert an error kind into a status code.
+fn status(kind: ErrorKind) -> i32 {
+    match kind {
+        ErrorKind::Unsupported | ErrorKind::WaylandNotEnabled => OWH_STATUS_UNSUPPORTED,
+        ErrorKind::Unavailable => OWH_STATUS_UNAVAILABLE,
+        ErrorKind::PlatformMismatch => OWH_STATUS_PLATFORM_MISMATCH,
+        _ => OWH_STATUS_ERROR,
+    }
+}
+
+/// Convert a C window handle into a raw window handle.
+fn from_c(raw: &OwhRawWindowHandle) -> Option<RawWindowHandle> {
+    let ptr = || NonNull::new(raw.window as *mut c_void);
+
+    let handle = match raw.kind {
+        OWH_KIND_XLIB => {
+            // `unsigned long` is only 32 bits wide on 64-bit Windows.
+            let mut xlib = XlibWindowHandle::new(c_ulong::try_from(raw.window).ok()?);
+            xlib.visual_id = c_ulong::try_from(raw.extra).ok()?;
+            xlib.into()
+        }
+        OWH_KIND_XCB => {
+            let mut xcb = XcbWindowHandle::new(NonZeroU32::new(u32::try_from(raw.window).ok()?)?);
+            xcb.visual_id = NonZeroU32::new(u32::try_from(raw.extra).ok()?);
+            xcb.into()
+        }
+        OWH_KIND_WAYLAND => WaylandWindowHandle::new(ptr()?).into(),
+        OWH_KIND_DRM => DrmWindowHandle::new(u32::try_from(raw.window).ok()?).into(),
+        OWH_KIND_GBM => GbmWindowHandle::new(ptr()?).into(),
+        OWH_KIND_WIN32 => {
+            let mut win32 = Win32WindowHandle::new(NonZeroIsize::new(raw.window as isize)?);
+            win32.hinstance = NonZeroIsize::new(raw.extra as isize);
+            win32.into()
+        }
+        OWH_KIND_WINRT => WinRtWindowHandle::new(ptr()?).into(),
+        OWH_KIND_APPKIT => AppKitWindowHandle::new(ptr()?).into(),
+        OWH_KIND_UIKIT => UiKitWindowHandle::new(ptr()?).into(),
+        OWH_KIND_ANDROID_NDK => AndroidNdkWindowHandle::new(ptr()?).into(),
+        OWH_KIND_OHOS_NDK => OhosNdkWindowHandle::new(ptr()?).into(),
+        OWH_KIND_HAIKU => HaikuWindowHandle::new(ptr()?).into(),
+        OWH_KIND_ORBITAL => OrbitalWindowHandle::new(ptr()?).into(),
+        OWH_KIND_WEB => WebWindowHandle::new(u32::try_from(raw.window).ok()?).into(),
+        OWH_KIND_WEB_CANVAS => WebCanvasWindowHandle::new(raw.window).into(),
+        OWH_KIND_WEB_OFFSCREEN_CANVAS => WebOffscreenCanvasWindowHandle::new(raw.window).into(),
+        _ => return None,
+    };
+
+    Some(handle)
+}
+
+/// Convert a raw window handle into a C window handle.
+fn to_c(raw: RawWindowHandle) -> Option<OwhRawWindowHandle> {
+    let (kind, window, extra) = match raw {
+        RawWindowHandle::Xlib(xlib) => {
+            (OWH_KIND_XLIB, xlib.window as usize, xlib.visual_id as usize)
+        }
+        RawWindowHandle::Xcb(xcb) => (
+            OWH_KIND_XCB,
+            xcb.window.get() as usize,
+            xcb.visual_id.map_or(0, |id| id.get() as usize),
+        ),
+        RawWindowHandle::Wayland(wayland) => {
+            (OWH_KIND_WAYLAND, wayland.surface.as_ptr() as usize, 0)
+        }
+        RawWindowHandle::Drm(drm) => (OWH_KIND_DRM, drm.plane as usize, 0),
+        RawWindowHandle::Gbm(gbm) => (OWH_KIND_GBM, gbm.gbm_surface.as_ptr() as usize, 0),
+        RawWindowHandle::Win32(win32) => (
+            OWH_KIND_WIN32,
+            win32.hwnd.get() as usize,
+            win32
+                .hinstance
+                .map_or(0, |hinstance| hinstance.get() as usize),
+        ),
+        RawWindowHandle::WinRt(winrt) => (OWH_KIND_WINRT, winrt.core_window.as_ptr() as usize, 0),
+        RawWindowHandle::AppKit(appkit) => (OWH_KIND_APPKIT, appkit.ns_view.as_ptr() as usize, 0),
+        RawWindowHandle::UiKit(uikit) => (OWH_KIND_UIKIT, uikit.ui_view.as_ptr() as usize, 0),
+        RawWindowHandle::AndroidNdk(android) => (
+            OWH_KIND_ANDROID_NDK,
+            android.a_native_window.as_ptr() as usize,
+            0,
+        ),
+        RawWindowHandle::OhosNdk(ohos) => {
+            (OWH_KIND_OHOS_NDK, ohos.native_window.as_ptr() as usize, 0)
+        }
+        RawWindowHandle::Haiku(haiku) => (OWH_KIND_HAIKU, haiku.b_window.as_ptr() as usize, 0),
+        RawWindowHandle::Orbital(orbital) => {
+            (OWH_KIND_ORBITAL, orbital.window.as_ptr() as usize, 0)
+        }
+        RawWindowHandle::Web(web) => (OWH_KIND_WEB, web.id as usize, 0),
+        RawWindowHandle::WebCanvas(web) => (OWH_KIND_WEB_CANVAS, web.obj, 0),
+        RawWindowHandle::WebOffscreenCanvas(web) => (OWH_KIND_WEB_OFFSCREEN_CANVAS, web.obj, 0),
+        _ => return None,
+    };
+
+    Some(OwhRawWindowHandle {
+        kind,
+        window,
+        extra,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::ptr;
+
+    #[test]
+    fn win32_roundtrip() {
+        let raw = OwhRawWindowHandle {
+            kind: OWH_KIND_WIN32,
+            window: 0x1234,
+            extra: 0x5678,
+        };
+
+        let mut handle = ptr::null_mut();
+        let mut clone = ptr::null_mut();
+        let mut out = OwhRawWindowHandle {
+            kind: 0,
+            window: 0,
+            extra: 0,
+        };
+
+        // SAFETY: Every pointer is valid, and each handle is freed exactly once.
+        unsafe {
+            assert_eq!(owh_window_handle_new(&raw, &mut handle), OWH_STATUS_OK);
+            assert_eq!(owh_window_handle_clone(handle, &mut clone), OWH_STATUS_OK);
+            assert_eq!(owh_window_handle_free(handle), OWH_STATUS_OK);
+
+            assert_eq!(owh_window_handle_raw(clone, &mut out), OWH_STATUS_OK);
+            assert_eq!(owh_window_handle_free(clone), OWH_STATUS_OK);
+        }
+
+        assert_eq!(out.kind, OWH_KIND_WIN32);
+        assert_eq!(out.window, raw.window);
+        assert_eq!(out.extra, raw.extra);
+    }
+
+    #[test]
+    fn panics_are_caught() {
+        assert_eq!(guard(|| OWH_STATUS_OK), OWH_STATUS_OK);
+        assert_eq!(guard(|| panic!("injected failure")), OWH_STATUS_PANIC);
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Owned window and display handles bundled together.
+
+use crate::{Error, OwnedDisplayHandle, OwnedWindowHandle};
+
+use raw_window_handle::{
+    DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, WindowHandle,
+};
+
+/// An owned window handle together with the display it belongs to.
+///
+/// This implements both [`HasWindowHandle`] and [`HasDisplayHandle`], which is what GPU APIs
+/// such as `wgpu` expect when creating a surface.
+///
+/// ```
+/// use owned_window_handle::raw_window_handle::{
+///     DisplayHandle, HasDisplayHandle, HasWindowHandle, XlibDisplayHandle,
+/// };
+/// use owned_window_handle::{OwnedDisplayHandle, OwnedWindowHandle};
+///
+/// // SAFETY: This display is never connected to.
+/// let display = unsafe { DisplayHandle::borrow_raw(XlibDisplayHandle::new(None, 0).into()) };
+/// let display = OwnedDisplayHandle::new(display).unwrap();
+/// let window = OwnedWindowHandle::from_xlib(1, 0);
+///
+/// // Anything that wants a surface target can take the combined handle.
+/// fn surface_target(_target: impl HasWindowHandle + HasDisplayHandle + 'static) {}
+/// surface_target(window.with_display(display));
+/// ```
+#[derive(Debug)]
+pub struct OwnedWindowDisplayHandle {
+    /// The window.
+    window: OwnedWindowHandle,
+
+    /// The display the window belongs to.
+    display: OwnedDisplayHandle,
+}
+
+impl OwnedWindowDisplayHandle {
+    /// Bundle a window with the display it belongs to.
+    #[inline]
+    pub fn new(window: OwnedWindowHandle, display: OwnedDisplayHandle) -> Self {
+        Self { window, display }
+    }
+
+    /// Take ownership of both the window and the display of something.
+    #[inline]
+    pub fn from_window<W: HasWindowHandle + HasDisplayHandle>(handle: &W) -> Result<Self, Error> {
+        Ok(Self {
+            window: OwnedWindowHandle::new(handle)?,
+            display: OwnedDisplayHandle::new(handle)?,
+        })
+    }
+
+    /// Get the window.
+    #[inline]
+    pub fn window(&self) -> &OwnedWindowHandle {
+        &self.window
+    }
+
+    /// Get the display.
+    #[inline]
+    pub fn display(&self) -> &OwnedDisplayHandle {
+        &self.display
+    }
+
+    /// Split this back into the window and the display.
+    #[inline]
+    pub fn into_parts(self) -> (OwnedWindowHandle, OwnedDisplayHandle) {
+        (self.window, self.display)
+    }
+
+    /// Clone both the window and the display.
+    ///
+    /// If cloning the display fails, the cloned window is released again.
+    #[inline]
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        let window = self.window.try_clone()?;
+        let display = self.display.try_clone()?;
+        Ok(Self { window, display })
+    }
+}
+
+impl HasWindowHandle for OwnedWindowDisplayHandle {
+    #[inline]
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        self.window.window_handle()
+    }
+}
+
+impl HasDisplayHandle for OwnedWindowDisplayHandle {
+    #[inline]
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        self.display.display_handle()
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Owned display handles.
+
+use crate::{wayland, Error};
+
+use core::fmt;
+use raw_window_handle::{DisplayHandle, HandleError, HasDisplayHandle, RawDisplayHandle};
+
+/// An owned equivalent of the display handle.
+///
+/// This is the display counterpart to [`OwnedWindowHandle`](crate::OwnedWindowHandle).
+///
+/// Only Wayland displays are actually kept alive, through `wayland-backend`. Xlib and XCB
+/// have no way of refcounting a connection, so their displays are just copied, and this
+/// handle does not extend their lifetime. The caller must not call `XCloseDisplay` or
+/// `xcb_disconnect` while any handle to the display exists. The same goes for DRM file
+/// descriptors and GBM devices.
+pub struct OwnedDisplayHandle {
+    /// Underlying implementation.
+    imp: Impl,
+}
+
+/// Underlying implementation.
+enum Impl {
+    /// Static display that can be copied around.
+    ///
+    /// Every backend except for Wayland uses this.
+    Direct(DisplayHandle<'static>),
+
+    /// Wayland display tracked by `wayland-backend`.
+    Wayland(wayland::WaylandDisplay),
+}
+
+// SAFETY: Display handles are either plain ID's or pointers that we only copy around, and
+// `wayland-backend`'s types are `Send` and `Sync`.
+unsafe impl Send for OwnedDisplayHandle {}
+
+// SAFETY: See above, none of the methods taking `&self` touch thread-local state.
+unsafe impl Sync for OwnedDisplayHandle {}
+
+impl fmt::Debug for OwnedDisplayHandle {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedDisplayHandle").finish_non_exhaustive()
+    }
+}
+
+impl OwnedDisplayHandle {
+    /// Create a new [`OwnedDisplayHandle`] from something that implements [`HasDisplayHandle`].
+    #[inline]
+    pub fn new(handle: impl HasDisplayHandle) -> Result<Self, Error> {
+        Self::_new(handle.display_handle()?)
+    }
+
+    fn _new(handle: DisplayHandle<'_>) -> Result<Self, Error> {
+        Ok(Self {
+            imp: inc_refcount(handle)?,
+        })
+    }
+
+    /// Clone this display handle.
+    #[inline]
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        match &self.imp {
+            Impl::Direct(handle) => {
+                // Just increment refcount on the handle.
+                Self::_new(*handle)
+            }
+
+            Impl::Wayland(wayland) => {
+                // wayland-backend's objects can be cheaply cloned.
+                Ok(Self {
+                    imp: Impl::Wayland(wayland.clone()),
+                })
+            }
+        }
+    }
+}
+
+impl Drop for OwnedDisplayHandle {
+    fn drop(&mut self) {
+        if let Impl::Direct(handle) = self.imp {
+            // SAFETY: Our handle was created via inc_refcount.
+            // Releasing a display never does anything that could fail, and panicking in a
+            // destructor would abort an unwinding thread.
+            let _ = unsafe { dec_refcount(handle) };
+        }
+    }
+}
+
+impl HasDisplayHandle for OwnedDisplayHandle {
+    #[inline]
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        match &self.imp {
+            Impl::Direct(handle) => Ok(*handle),
+            Impl::Wayland(wayland) => wayland::display_as_ptr(wayland),
+        }
+    }
+}
+
+/// Increment reference count of the underlying display.
+fn inc_refcount(display: DisplayHandle<'_>) -> Result<Impl, Error> {
+    let raw = match display.as_raw() {
+        RawDisplayHandle::Xlib(xlib) => {
+            // Xlib has no way of refcounting a `Display*`, so just copy the pointer. This does
+            // not keep the display alive, it is up to the caller to not call `XCloseDisplay`
+            // while this handle exists.
+            RawDisplayHandle::Xlib(xlib)
+        }
+
+        RawDisplayHandle::Xcb(xcb) => {
+            // Same deal as Xlib, except with `xcb_disconnect`.
+            RawDisplayHandle::Xcb(xcb)
+        }
+
+        RawDisplayHandle::Wayland(wayland) => {
+            // Wayland displays need to be tracked by wayland-backend.
+            // SAFETY: The display handle guarantees that the display is valid.
+            return Ok(Impl::Wayland(unsafe { wayland::clone_display(wayland) }?));
+        }
+
+        RawDisplayHandle::Drm(drm) => {
+            // DRM file descriptors are numeric ID's that the caller is expected to keep open.
+            RawDisplayHandle::Drm(drm)
+        }
+
+        RawDisplayHandle::Gbm(gbm) => {
+            // GBM devices are owned by the caller, we just copy the pointer.
+            RawDisplayHandle::Gbm(gbm)
+        }
+
+        RawDisplayHandle::Windows(windows) => {
+            // Windows has no display object.
+            RawDisplayHandle::Windows(windows)
+        }
+
+        RawDisplayHandle::AppKit(appkit) => {
+            // AppKit has no display object.
+            RawDisplayHandle::AppKit(appkit)
+        }
+
+        RawDisplayHandle::UiKit(uikit) => {
+            // UIKit has no display object.
+            RawDisplayHandle::UiKit(uikit)
+        }
+
+        RawDisplayHandle::Android(android) => {
+            // Android has no display object.
+            RawDisplayHandle::Android(android)
+        }
+
+        RawDisplayHandle::Web(web) => {
+            // The web has no display object.
+            RawDisplayHandle::Web(web)
+        }
+
+        RawDisplayHandle::Haiku(haiku) => {
+            // Haiku has no display object.
+            RawDisplayHandle::Haiku(haiku)
+        }
+
+        RawDisplayHandle::Orbital(orbital) => {
+            // Orbital has no display object.
+            RawDisplayHandle::Orbital(orbital)
+        }
+
+        RawDisplayHandle::Ohos(ohos) => {
+            // OpenHarmony has no display object.
+            RawDisplayHandle::Ohos(ohos)
+        }
+
+        // Default case: platform this version of the code doesn't anticipate.
+        _ => return Err(HandleError::NotSupported.into()),
+    };
+
+    // SAFETY: See above comments, this is always a valid handle.
+    Ok(Impl::Direct(unsafe { DisplayHandle::borrow_raw(raw) }))
+}
+
+/// Decrement reference count of the underlying display.
+///
+/// # Safety
+///
+/// `display` must have been created via [`inc_refcount`].
+unsafe fn dec_refcount(display: DisplayHandle<'static>) -> Result<(), Error> {
+    match display.as_raw() {
+        RawDisplayHandle::Xlib(_)
+        | RawDisplayHandle::Xcb(_)
+        | RawDisplayHandle::Drm(_)
+        | RawDisplayHandle::Gbm(_)
+        | RawDisplayHandle::Windows(_)
+        | RawDisplayHandle::AppKit(_)
+        | RawDisplayHandle::UiKit(_)
+        | RawDisplayHandle::Android(_)
+        | RawDisplayHandle::Web(_)
+        | RawDisplayHandle::Haiku(_)
+        | RawDisplayHandle::Orbital(_)
+        | RawDisplayHandle::Ohos(_) => {
+            // We did nothing with the display above, so no need to do anything
+            // here either.
+        }
+
+        RawDisplayHandle::Wayland(_) => {
+            // inc_refcount never creates this variant, since Wayland objects are tracked by
+            // wayland-backend instead. There is no reference to release, so don't panic.
+        }
+
+        // Default case: platform this version of the code doesn't anticipate.
+        _ => return Err(HandleError::NotSupported.into()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! The window handle types that this build can take ownership of.
+//!
+//! These are re-exported from [`raw_window_handle`], but only on targets and with features
+//! where [`is_platform_supported`](crate::is_platform_supported) returns `true` for them. This
+//! makes using an unsupported window handle a compile error rather than a runtime one.
+//!
+//! ```
+//! use owned_window_handle::handles::XlibWindowHandle;
+//! use owned_window_handle::OwnedWindowHandle;
+//!
+//! let xlib = XlibWindowHandle::new(1);
+//! let handle = OwnedWindowHandle::from_xlib(xlib.window, xlib.visual_id);
+//! ```
+
+pub use raw_window_handle::{Win32WindowHandle, XcbWindowHandle, XlibWindowHandle};
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub use raw_window_handle::{DrmWindowHandle, GbmWindowHandle};
+
+#[cfg(all(
+    feature = "wayland",
+    unix,
+    not(any(
+        target_os = "redox",
+        target_family = "wasm",
+        target_os = "android",
+        target_vendor = "apple"
+    ))
+))]
+pub use raw_window_handle::WaylandWindowHandle;
+
+#[cfg(windows)]
+pub use raw_window_handle::WinRtWindowHandle;
+
+#[cfg(all(target_vendor = "apple", feature = "appkit"))]
+pub use raw_window_handle::{AppKitWindowHandle, UiKitWindowHandle};
+
+#[cfg(all(target_os = "android", feature = "android"))]
+pub use raw_window_handle::AndroidNdkWindowHandle;
+
+#[cfg(target_env = "ohos")]
+pub use raw_window_handle::OhosNdkWindowHandle;
+
+#[cfg(target_os = "haiku")]
+pub use raw_window_handle::HaikuWindowHandle;
+
+#[cfg(target_os = "redox")]
+pub use raw_window_handle::OrbitalWindowHandle;
+
+#[cfg(all(target_family = "wasm", feature = "web"))]
+pub use raw_window_handle::{
+    WebCanvasWindowHandle, WebOffscreenCanvasWindowHandle, WebWindowHandle,
+};
@@ -0,0 +1,55 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Global hooks observing the lifecycle of window handles.
+
+use crate::RawKind;
+
+use std::sync::{Arc, RwLock};
+
+/// A lifecycle event of an [`OwnedWindowHandle`](crate::OwnedWindowHandle).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HookEvent {
+    /// A handle to a window of this kind was created.
+    Created(RawKind),
+
+    /// A handle to a window of this kind was dropped.
+    ///
+    /// This is also emitted for handles passed to
+    /// [`OwnedWindowHandle::into_raw`](crate::OwnedWindowHandle::into_raw).
+    Dropped(RawKind),
+}
+
+/// The type of a hook.
+type Hook = Arc<dyn Fn(HookEvent) + Send + Sync>;
+
+/// The currently registered hook.
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// Register a hook that is called whenever an [`OwnedWindowHandle`] is created or dropped.
+///
+/// This replaces the previously registered hook, if any. The hook is called on the thread
+/// that creates or drops the handle, without holding any locks, so it may create and drop
+/// handles or call `set_global_hook` itself. A hook that is replaced while it is running
+/// still finishes that call.
+///
+/// [`OwnedWindowHandle`]: crate::OwnedWindowHandle
+pub fn set_global_hook(hook: impl Fn(HookEvent) + Send + Sync + 'static) {
+    let mut slot = HOOK.write().unwrap_or_else(|err| err.into_inner());
+    *slot = Some(Arc::new(hook));
+}
+
+/// Call the registered hook, if there is one.
+pub(crate) fn fire(event: HookEvent) {
+    // Don't hold the lock while calling the hook, in case it registers another hook.
+    let hook = HOOK.read().unwrap_or_else(|err| err.into_inner()).clone();
+    if let Some(hook) = hook {
+        hook(event);
+    }
+}
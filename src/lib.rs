@@ -9,20 +9,204 @@
 //! Take ownership of window handles passed in via [`raw-window-handle`].
 //!
 //! [`raw-window-handle`]: https://crates.io/crates/raw-window-handle
+//!
+//! # Features
+//!
+//! - `objc2` (enabled by default): on Apple platforms, message views through [`objc2`].
+//!   This implies `appkit`.
+//!   Without it, the Objective-C runtime is called directly. This avoids the dependency,
+//!   at the cost of relying on hand-written bindings to `libobjc`.
+//!
+//! - `appkit`, `android` and `web` (enabled by default): support AppKit and UIKit views,
+//!   Android windows and web canvases respectively. Without them, handles for these
+//!   platforms are rejected with [`ErrorKind::Unsupported`], and `ndk-sys`, `wasm-bindgen`
+//!   and `web-sys` aren't pulled in.
+//!
+//! - `hooks`: observe handles being created and dropped, see [`set_global_hook`].
+//!
+//! - `winit`: add [`OwnedWindowHandle::matches_window`] for comparing against [`winit`]
+//!   windows.
+//!
+//! - `tracing`: emit [`tracing`] events whenever a reference to a window is acquired or
+//!   released, to help track down leaks.
+//!
+//! - `leak-check`: count the window references that are currently held, see
+//!   [`outstanding_handles`].
+//!
+//! - `double-drop-check`: with debug assertions enabled, panic when a bitwise copy of an
+//!   [`OwnedWindowHandle`] is dropped, before it releases the window a second time. This
+//!   keeps track of every live handle, so it is best left to tests.
+//!
+//! [`objc2`]: https://crates.io/crates/objc2
+//! [`tracing`]: https://crates.io/crates/tracing
+//! [`winit`]: https://crates.io/crates/winit
+
+#![forbid(unsafe_op_in_unsafe_fn)]
 
-use core::fmt;
-use raw_window_handle::{HandleError, HasWindowHandle, RawWindowHandle, WindowHandle};
+use core::any::Any;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::{fmt, mem, ptr};
+use raw_window_handle::{
+    HandleError, HasDisplayHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle, WindowHandle,
+};
+use std::sync::Arc;
 
+pub use borrowed::BorrowedWindowHandle;
+pub use combined::OwnedWindowDisplayHandle;
+pub use display::OwnedDisplayHandle;
+#[cfg(feature = "hooks")]
+pub use hooks::{set_global_hook, HookEvent};
 pub use raw_window_handle;
+pub use weak::WeakWindowHandle;
+
+/// Emit a trace event if the `tracing` feature is enabled.
+macro_rules! trace {
+    ($($tt:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($tt)*);
+    }};
+}
+
+mod borrowed;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod combined;
+mod display;
+pub mod handles;
+#[cfg(feature = "hooks")]
+mod hooks;
+pub mod platform;
+
+#[cfg(feature = "raw-window-handle-05")]
+mod rwh05;
+#[cfg(feature = "serde")]
+mod serialize;
+mod weak;
 
 /// An owned equivalent of the window handle.
 ///
 /// See [crate level documentation](crate) for more information.
+///
+/// # Thread safety
+///
+/// On Windows, `HWND`s are ID's into a thread local table, so window handles are bound to
+/// the thread that created them. [`OwnedWindowHandle`] is neither [`Send`] nor [`Sync`] there.
+///
+/// On Apple platforms, AppKit and UIKit views may only be retained and released on the main
+/// thread. Creating, cloning or dropping a handle to one of them on any other thread fails
+/// with [`ErrorKind::NotMainThread`]. A handle dropped on another thread leaks its reference
+/// instead of releasing it. To keep this from happening by accident, [`OwnedWindowHandle`]
+/// is neither [`Send`] nor [`Sync`] there either.
+///
+/// On the web, canvases are JavaScript objects that live on the thread that created them, so
+/// [`OwnedWindowHandle`] is neither [`Send`] nor [`Sync`].
+///
+/// On every other platform, [`OwnedWindowHandle`] is both [`Send`] and [`Sync`].
+///
+/// # Duplicating handles
+///
+/// Every [`OwnedWindowHandle`] releases its reference to the window when it is dropped.
+/// Bitwise copies made using [`ptr::read`](core::ptr::read) or similar release the same
+/// reference twice, which can free the window while it is still in use. Use
+/// [`OwnedWindowHandle::try_clone`] instead. With debug assertions and the `double-drop-check`
+/// feature enabled, dropping such a copy panics before the reference is released a second
+/// time.
 pub struct OwnedWindowHandle {
     /// Underlying implementation.
     imp: Impl,
+
+    /// Unique ID used to catch handles being dropped twice.
+    #[cfg(all(debug_assertions, feature = "double-drop-check"))]
+    id: usize,
+
+    /// Marker restricting which threads this handle can be used on.
+    _marker: PhantomData<ThreadMarker>,
+}
+
+/// Marker type for windows that are bound to their thread.
+#[cfg(any(windows, target_vendor = "apple", target_family = "wasm"))]
+type ThreadMarker = *const ();
+
+/// Marker type for windows that aren't bound to their thread.
+#[cfg(not(any(windows, target_vendor = "apple", target_family = "wasm")))]
+type ThreadMarker = ();
+
+// SAFETY: On these platforms, windows are either plain ID's, pointers that we only ever copy
+// around, or objects with thread-safe reference counts (`ANativeWindow` and OpenHarmony's
+// `OHNativeWindow`). `wayland-backend`'s types are `Send` and `Sync` and shared windows are
+// required to be `Send` and `Sync`.
+#[cfg(not(any(windows, target_vendor = "apple", target_family = "wasm")))]
+unsafe impl Send for OwnedWindowHandle {}
+
+// SAFETY: See above, none of the methods taking `&self` touch thread-local state.
+#[cfg(not(any(windows, target_vendor = "apple", target_family = "wasm")))]
+unsafe impl Sync for OwnedWindowHandle {}
+
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[allow(dead_code)]
+    fn assert_handles() {
+        // Displays are never bound to a thread.
+        assert_send_sync::<OwnedDisplayHandle>();
+
+        #[cfg(not(any(windows, target_vendor = "apple", target_family = "wasm")))]
+        {
+            assert_send_sync::<OwnedWindowHandle>();
+            assert_send_sync::<OwnedWindowDisplayHandle>();
+            assert_send_sync::<WeakWindowHandle>();
+        }
+    }
+};
+
+/// Fail to compile if a type implements a trait.
+///
+/// If `$ty` implements the trait, both impls below apply to it, and the call to `some_item`
+/// becomes ambiguous.
+#[cfg(any(windows, target_vendor = "apple", target_family = "wasm"))]
+macro_rules! assert_not_impl {
+    ($ty:ty: $($trait:tt)+) => {
+        const _: fn() = || {
+            trait AmbiguousIfImpl<A> {
+                fn some_item() {}
+            }
+
+            impl<T: ?Sized> AmbiguousIfImpl<()> for T {}
+
+            struct Invalid;
+            impl<T: ?Sized + $($trait)+> AmbiguousIfImpl<Invalid> for T {}
+
+            let _ = <$ty as AmbiguousIfImpl<_>>::some_item;
+        };
+    };
 }
 
+#[cfg(any(windows, target_vendor = "apple", target_family = "wasm"))]
+assert_not_impl!(OwnedWindowHandle: Send);
+
+#[cfg(any(windows, target_vendor = "apple", target_family = "wasm"))]
+assert_not_impl!(OwnedWindowHandle: Sync);
+
+#[cfg(any(windows, target_vendor = "apple", target_family = "wasm"))]
+assert_not_impl!(OwnedWindowDisplayHandle: Send);
+
+#[cfg(any(windows, target_vendor = "apple", target_family = "wasm"))]
+assert_not_impl!(OwnedWindowDisplayHandle: Sync);
+
+#[cfg(any(windows, target_vendor = "apple", target_family = "wasm"))]
+assert_not_impl!(WeakWindowHandle: Send);
+
+#[cfg(any(windows, target_vendor = "apple", target_family = "wasm"))]
+assert_not_impl!(WeakWindowHandle: Sync);
+
+/// A window owned by an [`OwnedWindowHandle`].
+struct OwnedWindow<W>(W);
+
+// SAFETY: The window is never accessed through a shared reference once it is stored, so it
+// only needs to be `Send`.
+unsafe impl<W: Send> Sync for OwnedWindow<W> {}
+
 /// Underlying implementation.
 enum Impl {
     /// Static window that can be refcounted.
@@ -32,12 +216,65 @@ enum Impl {
 
     /// Direct Wayland object ID.
     Wayland(wayland::WaylandHandle),
+
+    /// X11 window along with the connection it belongs to.
+    X11 {
+        /// The Xlib or XCB window.
+        window: WindowHandle<'static>,
+
+        /// The display connection for the window.
+        display: OwnedDisplayHandle,
+    },
+
+    /// Window kept alive by a shared pointer instead of its reference count.
+    Shared {
+        /// The window handle borrowed from the window.
+        handle: WindowHandle<'static>,
+
+        /// The window itself.
+        window: Arc<dyn Any + Send + Sync>,
+    },
+
+    /// Window that the caller promised can be copied without any refcounting.
+    Assumed(WindowHandle<'static>),
 }
 
 impl fmt::Debug for OwnedWindowHandle {
-    #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("OwnedWindowHandle").finish_non_exhaustive()
+        let mut s = f.debug_struct("OwnedWindowHandle");
+        s.field("platform", &self.platform());
+
+        match &self.imp {
+            Impl::Direct(handle)
+            | Impl::X11 { window: handle, .. }
+            | Impl::Shared { handle, .. }
+            | Impl::Assumed(handle) => match handle.as_raw() {
+                RawWindowHandle::Xlib(xlib) => {
+                    s.field("window", &xlib.window);
+                }
+                RawWindowHandle::Xcb(xcb) => {
+                    s.field("window", &xcb.window);
+                }
+                RawWindowHandle::Win32(win32) => {
+                    s.field("hwnd", &win32.hwnd);
+                }
+                RawWindowHandle::Drm(drm) => {
+                    s.field("plane", &drm.plane);
+                }
+                raw => {
+                    // Everything else is identified by a pointer.
+                    if let Some(ptr) = window_id(&raw) {
+                        s.field("ptr", &format_args!("{:#x}", ptr));
+                    }
+                }
+            },
+
+            Impl::Wayland(wayland) => {
+                s.field("surface", wayland);
+            }
+        }
+
+        s.finish_non_exhaustive()
     }
 }
 
@@ -45,378 +282,3985 @@ impl OwnedWindowHandle {
     /// Create a new [`OwnedWindowHandle`] from something that implements [`HasWindowHandle`].
     #[inline]
     pub fn new(handle: impl HasWindowHandle) -> Result<Self, Error> {
-        Self::_new(handle.window_handle()?)
+        Self::from_window_handle(handle.window_handle()?)
     }
 
-    fn _new(handle: WindowHandle<'_>) -> Result<Self, Error> {
-        Ok(Self {
-            imp: inc_refcount(handle)?,
-        })
+    /// Create a new [`OwnedWindowHandle`], or `None` if the platform isn't supported.
+    ///
+    /// Returns `Ok(None)` if the error kind is [`ErrorKind::Unsupported`] or
+    /// [`ErrorKind::PlatformMismatch`]. Every other error is returned as is.
+    pub fn try_new<W: HasWindowHandle>(handle: &W) -> Result<Option<Self>, Error> {
+        match Self::new(handle) {
+            Ok(handle) => Ok(Some(handle)),
+            Err(err) => match err.kind() {
+                ErrorKind::Unsupported | ErrorKind::PlatformMismatch => Ok(None),
+                _ => Err(err),
+            },
+        }
     }
 
-    /// Clone this window handle.
-    #[inline]
-    pub fn try_clone(&self) -> Result<Self, Error> {
-        match &self.imp {
-            Impl::Direct(handle) => {
-                // Just increment refcount on the handle.
-                Self::_new(*handle)
-            }
+    /// Create a new [`OwnedWindowHandle`], checking that the window is valid first.
+    ///
+    /// [`OwnedWindowHandle::new`] trusts the window handle to point to a valid window. This
+    /// does some cheap checks first, for handles that come from untrusted sources such as
+    /// foreign code:
+    ///
+    /// - AppKit and UIKit views must be instances of `NSView` and `UIView` respectively.
+    /// - Win32 windows must pass `IsWindow`.
+    /// - Wayland surfaces must be `wl_surface`s. This is always checked.
+    ///
+    /// If a check fails, an error of kind [`ErrorKind::InvalidHandle`] is returned. Other
+    /// platforms aren't checked any further than in [`OwnedWindowHandle::new`].
+    ///
+    /// The AppKit and UIKit checks message the view, so they catch objects of the wrong
+    /// class but not dangling or bogus pointers. Those crash no matter what is sent to them.
+    pub fn new_checked<W: HasWindowHandle>(handle: &W) -> Result<Self, Error> {
+        let handle = handle.window_handle()?;
+        validate(&handle.as_raw())?;
+        Self::from_window_handle(handle)
+    }
 
-            Impl::Wayland(wayland) => {
-                // wayland-backend's objects can be cheaply cloned.
-                Ok(Self {
-                    imp: Impl::Wayland(wayland.clone()),
-                })
-            }
+    /// Create several [`OwnedWindowHandle`]s for the same window.
+    ///
+    /// The window handle is only resolved once, and the rest of the handles are cheap clones
+    /// of the first one. If any of them fails to be created, the ones created so far are
+    /// released.
+    pub fn new_batch<W: HasWindowHandle>(handle: &W, count: usize) -> Result<Vec<Self>, Error> {
+        let mut handles = Vec::with_capacity(count);
+        if count == 0 {
+            return Ok(handles);
+        }
+
+        handles.push(Self::new(handle)?);
+        for _ in 1..count {
+            let clone = handles[0].try_clone()?;
+            handles.push(clone);
         }
+
+        Ok(handles)
     }
-}
 
-impl Drop for OwnedWindowHandle {
-    fn drop(&mut self) {
-        if let Impl::Direct(handle) = self.imp {
-            // SAFETY: Our handle was created via inc_refcount.
-            let _result = unsafe { dec_refcount(handle) };
+    /// Create a new [`OwnedWindowHandle`], retrying if the window handle is unavailable.
+    ///
+    /// Windows that are still being initialized may briefly return
+    /// [`HandleError::Unavailable`]. This calls [`HasWindowHandle::window_handle`] up to
+    /// `attempts` times, sleeping for `backoff` between each try. Any other error is returned
+    /// immediately. At least one attempt is always made.
+    #[cfg(feature = "std")]
+    pub fn new_with_retry(
+        handle: impl HasWindowHandle,
+        attempts: usize,
+        backoff: std::time::Duration,
+    ) -> Result<Self, Error> {
+        let mut remaining = attempts.max(1);
 
-            #[cfg(debug_assertions)]
-            _result.unwrap();
+        loop {
+            remaining -= 1;
+
+            match handle.window_handle() {
+                Ok(handle) => return Self::from_window_handle(handle),
+                Err(HandleError::Unavailable) if remaining > 0 => std::thread::sleep(backoff),
+                Err(err) => return Err(err.into()),
+            }
         }
     }
-}
 
-impl HasWindowHandle for OwnedWindowHandle {
-    #[inline]
-    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
-        match &self.imp {
-            Impl::Direct(handle) => Ok(*handle),
-            Impl::Wayland(wayland) => wayland::as_ptr(wayland),
+    /// Create a new [`OwnedWindowHandle`], asynchronously retrying if the window handle is
+    /// unavailable.
+    ///
+    /// This is the asynchronous version of [`OwnedWindowHandle::new_with_retry`]. Instead of
+    /// blocking the thread, it awaits the future returned by `sleep(backoff)` between each
+    /// try. This way, it works with any async runtime.
+    #[cfg(feature = "async")]
+    pub async fn new_async<W, S, F>(
+        handle: &W,
+        attempts: usize,
+        backoff: core::time::Duration,
+        sleep: S,
+    ) -> Result<Self, Error>
+    where
+        W: HasWindowHandle,
+        S: Fn(core::time::Duration) -> F,
+        F: core::future::Future<Output = ()>,
+    {
+        let mut remaining = attempts.max(1);
+
+        loop {
+            remaining -= 1;
+
+            match handle.window_handle() {
+                Ok(handle) => return Self::from_window_handle(handle),
+                Err(HandleError::Unavailable) if remaining > 0 => sleep(backoff).await,
+                Err(err) => return Err(err.into()),
+            }
         }
     }
-}
-
-/// Error type for window handles.
-#[derive(Debug)]
-pub struct Error(Repr);
 
-impl From<HandleError> for Error {
+    /// Create a new [`OwnedWindowHandle`] from a borrowed [`WindowHandle`].
+    ///
+    /// This acquires a new reference to the window, so `handle` only needs to be valid for
+    /// the duration of this call. Unlike [`OwnedWindowHandle::new`], this doesn't need a type
+    /// that implements [`HasWindowHandle`], which is useful for libraries that hand out
+    /// [`WindowHandle`]s directly.
+    ///
+    /// ```
+    /// use owned_window_handle::raw_window_handle::{WindowHandle, XlibWindowHandle};
+    /// use owned_window_handle::OwnedWindowHandle;
+    ///
+    /// // SAFETY: Xlib windows are just numeric ID's.
+    /// let window = unsafe { WindowHandle::borrow_raw(XlibWindowHandle::new(1).into()) };
+    ///
+    /// let owned = OwnedWindowHandle::from_window_handle(window).unwrap();
+    /// assert_eq!(owned.as_raw(), window.as_raw());
+    /// ```
     #[inline]
-    fn from(err: HandleError) -> Self {
-        Self(Repr::Raw(err))
+    pub fn from_window_handle(handle: WindowHandle<'_>) -> Result<Self, Error> {
+        Ok(Self::from_impl(inc_refcount(handle)?))
     }
-}
 
-impl fmt::Display for Error {
+    /// Create a new [`OwnedWindowHandle`] from a bare [`RawWindowHandle`].
+    ///
+    /// This saves wrapping the raw handle in a type that implements [`HasWindowHandle`].
+    /// Like [`OwnedWindowHandle::new`], this acquires a new reference to the window. To take
+    /// over a reference the caller already holds, use [`OwnedWindowHandle::from_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be a valid window handle for the duration of this call, under the same
+    /// rules as [`WindowHandle::borrow_raw`].
     #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.0 {
-            Repr::Raw(HandleError::NotSupported) => write!(f, "unsupported platform"),
-            Repr::Raw(HandleError::Unavailable) => write!(f, "window handle is unavailable"),
-            Repr::Raw(_) => write!(f, "unknown raw window handle error"),
-            Repr::CanvasNotFound(id) => write!(f, "canvas not found with id: {}", id),
-            Repr::MissingWebElements => write!(f, "missing web elements"),
-            Repr::PlatformMismatch { expected } => {
-                write!(f, "platform mismatch, expected: {}", expected)
-            }
-            Repr::RetainFailed => write!(f, "failed to retain window handle"),
-            Repr::WaylandNotEnabled => write!(f, "Wayland is not enabled"),
-            Repr::WaylandNotRust => write!(
-                f,
-                "the resulting Wayland handle was not created by Rust's `wayland-backend`"
-            ),
-        }
+    pub unsafe fn from_raw_window_handle(raw: RawWindowHandle) -> Result<Self, Error> {
+        // SAFETY: The caller guarantees that the handle is valid.
+        Self::from_window_handle(unsafe { WindowHandle::borrow_raw(raw) })
     }
-}
 
-#[cfg(feature = "std")]
-impl std::error::Error for Error {}
-
-/// Increment reference count of the underlying handle.
-fn inc_refcount(window: WindowHandle<'_>) -> Result<Impl, Error> {
-    let raw = match window.as_raw() {
-        RawWindowHandle::Xlib(xlib) => {
-            // Xlib windows are just numeric ID's and are safe to use after destruction.
-            RawWindowHandle::Xlib(xlib)
-        }
+    /// Create a new [`OwnedWindowHandle`] from a bare `wl_surface` pointer.
+    ///
+    /// This is a shorthand for building a [`WaylandWindowHandle`] by hand and passing it to
+    /// [`OwnedWindowHandle::from_raw_window_handle`]. Like any other Wayland surface, it must
+    /// have been created through Rust's `wayland-backend`, otherwise an error of kind
+    /// [`ErrorKind::WaylandNotRust`] is returned.
+    ///
+    /// # Safety
+    ///
+    /// `surface` must point to a live `wl_surface` proxy for the duration of this call.
+    ///
+    /// [`WaylandWindowHandle`]: raw_window_handle::WaylandWindowHandle
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "redox",
+            target_family = "wasm",
+            target_os = "android",
+            target_vendor = "apple"
+        ))
+    ))]
+    pub unsafe fn from_wayland_surface(
+        surface: ptr::NonNull<core::ffi::c_void>,
+    ) -> Result<Self, Error> {
+        let raw = raw_window_handle::WaylandWindowHandle::new(surface);
 
-        RawWindowHandle::Xcb(xcb) => {
-            // XCB windows are just numeric ID's and are safe to use after destruction.
-            RawWindowHandle::Xcb(xcb)
-        }
+        // SAFETY: The caller guarantees that the surface is valid.
+        let handle = unsafe { wayland::clone_handle(raw) }?;
+        Ok(Self::from_impl(Impl::Wayland(handle)))
+    }
 
-        RawWindowHandle::Win32(win32) => {
-            // Win32 windows are ID's into a thread local table.
-            // https://github.com/rust-windowing/raw-window-handle/issues/171#issuecomment-2282313064
-            RawWindowHandle::Win32(win32)
+    /// Create a new [`OwnedWindowHandle`] from an `NSWindow`.
+    ///
+    /// AppKit handles refer to views, so this takes the window's content view and retains
+    /// it, just like [`OwnedWindowHandle::new`] would. It is released when the handle is
+    /// dropped. Returns an error of kind [`ErrorKind::InvalidHandle`] if the window has no
+    /// content view, and [`ErrorKind::NotMainThread`] if this is not called on the main
+    /// thread.
+    ///
+    /// # Safety
+    ///
+    /// `window` must point to a valid `NSWindow`.
+    #[cfg(all(target_os = "macos", feature = "appkit"))]
+    pub unsafe fn from_ns_window(window: ptr::NonNull<core::ffi::c_void>) -> Result<Self, Error> {
+        // Windows must only be messaged from the main thread.
+        if !apple::is_main_thread() {
+            return Err(Error(Repr::NotMainThread));
         }
 
-        RawWindowHandle::Wayland(wayland) => {
-            // Wayland windows need to be tracked by wayland-backend.
-            return Ok(Impl::Wayland(unsafe { wayland::clone_handle(wayland) }?));
-        }
+        // SAFETY: The caller guarantees that this is a valid `NSWindow`.
+        let view = unsafe { apple::content_view(window) };
+        let view = ptr::NonNull::new(view).ok_or(Error(Repr::InvalidHandle))?;
 
-        RawWindowHandle::Drm(drm) => {
-            // DRM planes are just numeric ID's and are safe to use after destruction.
-            RawWindowHandle::Drm(drm)
+        // SAFETY: The content view is kept alive by the window for the duration of this call.
+        unsafe {
+            Self::from_raw_window_handle(raw_window_handle::AppKitWindowHandle::new(view).into())
         }
+    }
 
-        #[cfg(not(target_os = "android"))]
-        RawWindowHandle::AndroidNdk(_) => {
-            return Err(Error(Repr::PlatformMismatch {
-                expected: "android",
-            }))
+    fn from_impl(imp: Impl) -> Self {
+        #[cfg(feature = "leak-check")]
+        if matches!(imp, Impl::Direct(_)) {
+            leak_check::acquired();
         }
 
-        #[cfg(target_os = "android")]
-        RawWindowHandle::AndroidNdk(android) => {
-            // Use ANativeWindow_acquire to bump the reference count.
-            // SAFETY: `android` is a valid pointer to an `ANativeWindow`.
-            unsafe { ndk_sys::ANativeWindow_acquire(android.a_native_window.as_ptr().cast()) };
+        let handle = Self {
+            imp,
+            #[cfg(all(debug_assertions, feature = "double-drop-check"))]
+            id: poison::register(),
+            _marker: PhantomData,
+        };
 
-            RawWindowHandle::AndroidNdk(android)
-        }
+        #[cfg(feature = "hooks")]
+        hooks::fire(HookEvent::Created(handle.raw_window_handle_type()));
 
-        #[cfg(not(target_vendor = "apple"))]
-        RawWindowHandle::AppKit(_) | RawWindowHandle::UiKit(_) => {
-            return Err(Error(Repr::PlatformMismatch { expected: "apple" }))
-        }
+        handle
+    }
 
-        #[cfg(target_vendor = "apple")]
-        RawWindowHandle::AppKit(appkit) => {
-            use core::ptr::NonNull;
-            use objc2::runtime::NSObject;
+    /// Borrow a window handle without taking ownership of it.
+    ///
+    /// Unlike [`OwnedWindowHandle::new`], this doesn't acquire a reference to the window. The
+    /// returned [`BorrowedWindowHandle`] is tied to the lifetime of `handle` instead.
+    ///
+    /// ```
+    /// use owned_window_handle::raw_window_handle::{HasWindowHandle, WindowHandle, XlibWindowHandle};
+    /// use owned_window_handle::OwnedWindowHandle;
+    ///
+    /// // SAFETY: Xlib windows are just numeric ID's.
+    /// let window = unsafe { WindowHandle::borrow_raw(XlibWindowHandle::new(1).into()) };
+    ///
+    /// // Owning the window acquires a reference, so it can outlive `window`.
+    /// let owned = OwnedWindowHandle::new(window).unwrap();
+    ///
+    /// // Borrowing the window does no work, but can't outlive `window`.
+    /// let borrowed = OwnedWindowHandle::borrowed(&window);
+    ///
+    /// assert_eq!(
+    ///     owned.window_handle().unwrap().as_raw(),
+    ///     borrowed.window_handle().unwrap().as_raw(),
+    /// );
+    /// ```
+    #[inline]
+    pub fn borrowed<'a>(handle: &'a impl HasWindowHandle) -> BorrowedWindowHandle<'a> {
+        BorrowedWindowHandle::new(handle)
+    }
 
-            // Use the "retain" message to retain the object.
-            // SAFETY: We know this is a valid `NSView`.
-            let view: *mut NSObject = appkit.ns_view.as_ptr().cast();
-            let view: *mut NSObject = unsafe { objc2::msg_send![view, retain] };
+    /// Create a new [`OwnedWindowHandle`] that keeps a shared window alive.
+    ///
+    /// Rather than acquiring a reference to the window through the platform, this holds on
+    /// to `window` for as long as the handle or any of its clones are alive. This avoids
+    /// refcounting the window entirely.
+    pub fn borrowing<W>(window: Arc<W>) -> Result<Self, Error>
+    where
+        W: HasWindowHandle + Send + Sync + 'static,
+    {
+        let raw = window.window_handle()?.as_raw();
 
-            RawWindowHandle::AppKit(raw_window_handle::AppKitWindowHandle::new({
-                NonNull::new(view).ok_or(Error(Repr::RetainFailed))?.cast()
-            }))
-        }
+        // SAFETY: The window is kept alive by the `Arc` stored alongside the handle.
+        let handle = unsafe { WindowHandle::borrow_raw(raw) };
 
-        #[cfg(target_vendor = "apple")]
-        RawWindowHandle::UiKit(uikit) => {
-            use core::ptr::NonNull;
-            use objc2::runtime::NSObject;
+        Ok(Self::from_impl(Impl::Shared { handle, window }))
+    }
 
-            // Use the "retain" message to retain the object.
-            // SAFETY: We know this is a valid `UiView`.
-            let view: *mut NSObject = uikit.ui_view.as_ptr().cast();
-            let view: *mut NSObject = unsafe { objc2::msg_send![view, retain] };
+    /// Create a new [`OwnedWindowHandle`] that takes ownership of a window.
+    ///
+    /// This is like [`OwnedWindowHandle::borrowing`], except that the window doesn't need to be
+    /// shared. It is dropped once this handle and all of its clones are dropped.
+    pub fn owning<W>(window: W) -> Result<Self, Error>
+    where
+        W: HasWindowHandle + Send + 'static,
+    {
+        let window = Arc::new(OwnedWindow(window));
+        let raw = window.0.window_handle()?.as_raw();
 
-            RawWindowHandle::UiKit(raw_window_handle::UiKitWindowHandle::new({
-                NonNull::new(view).ok_or(Error(Repr::RetainFailed))?.cast()
-            }))
-        }
+        // SAFETY: The window is kept alive by the `Arc` stored alongside the handle.
+        let handle = unsafe { WindowHandle::borrow_raw(raw) };
 
-        #[cfg(not(target_family = "wasm"))]
-        RawWindowHandle::Web(_)
-        | RawWindowHandle::WebCanvas(_)
-        | RawWindowHandle::WebOffscreenCanvas(_) => {
-            return Err(Error(Repr::PlatformMismatch { expected: "wasm" }))
-        }
+        Ok(Self::from_impl(Impl::Shared { handle, window }))
+    }
 
-        #[cfg(target_family = "wasm")]
-        RawWindowHandle::Web(web) => {
-            use wasm_bindgen::convert::IntoWasmAbi;
+    /// Create a new [`OwnedWindowHandle`] for an X11 window, keeping its display alongside it.
+    ///
+    /// X11 window ID's are only meaningful in the context of the connection that created
+    /// them. This makes the handle carry the connection as well, so it can be retrieved using
+    /// [`OwnedWindowHandle::x11_display`].
+    ///
+    /// `window` and `display` must either both be Xlib handles or both be XCB handles. An
+    /// error of kind [`ErrorKind::PlatformMismatch`] is returned otherwise.
+    pub fn new_x11(
+        window: impl HasWindowHandle,
+        display: impl HasDisplayHandle,
+    ) -> Result<Self, Error> {
+        let window = window.window_handle()?;
+        let display = display.display_handle()?;
 
-            // Grab the current document.
-            let document = web_sys::window()
-                .ok_or(Error(Repr::MissingWebElements))?
-                .document()
-                .ok_or(Error(Repr::MissingWebElements))?;
+        match (window.as_raw(), display.as_raw()) {
+            (RawWindowHandle::Xlib(_), RawDisplayHandle::Xlib(_))
+            | (RawWindowHandle::Xcb(_), RawDisplayHandle::Xcb(_)) => {}
 
-            // Grab the element from its data segment.
-            let canvas: web_sys::Element = document
-                .query_selector(&format!("canvas[data-raw-handle=\"{}\"", web.id))
-                // `querySelector` only throws an error if the selector is invalid.
-                .unwrap()
-                .ok_or(Error(Repr::CanvasNotFound(web.id)))?;
+            // An Xlib window can't be used with an XCB connection and vice versa.
+            (RawWindowHandle::Xlib(_), RawDisplayHandle::Xcb(_)) => {
+                return Err(Error(Repr::PlatformMismatch { expected: "xlib" }))
+            }
+            (RawWindowHandle::Xcb(_), RawDisplayHandle::Xlib(_)) => {
+                return Err(Error(Repr::PlatformMismatch { expected: "xcb" }))
+            }
 
-            // The refcount is already bumped by query_selector, convert it down.
-            RawWindowHandle::WebCanvas(raw_window_handle::WebCanvasWindowHandle::new(
-                canvas.into_abi() as usize,
-            ))
+            _ => return Err(Error(Repr::PlatformMismatch { expected: "x11" })),
         }
 
-        #[cfg(target_family = "wasm")]
-        RawWindowHandle::WebCanvas(web) => {
-            use wasm_bindgen::convert::{IntoWasmAbi, RefFromWasmAbi};
+        let display = OwnedDisplayHandle::new(display)?;
 
-            // Get the underlying canvas.
-            // SAFETY: Guaranteed to be a valid `HtmlCanvasElement`.
-            let canvas = unsafe { web_sys::HtmlCanvasElement::ref_from_abi(web.obj as _) };
+        // SAFETY: X11 windows are just numeric ID's and are safe to use after destruction.
+        let window = unsafe { WindowHandle::borrow_raw(window.as_raw()) };
 
-            // Clone the underlying JS object so we own it.
-            let canvas = (&*canvas).clone();
+        Ok(Self::from_impl(Impl::X11 { window, display }))
+    }
 
-            // The refcount is already bumped by query_selector, convert it down.
-            RawWindowHandle::WebCanvas(raw_window_handle::WebCanvasWindowHandle::new(
-                canvas.into_abi() as usize,
-            ))
-        }
+    /// Create a handle to an Xlib window from its ID's.
+    ///
+    /// Xlib windows are plain numbers, so this is safe and can't fail. This is useful to get
+    /// a handle back after sending its ID's to another process. A `visual_id` of zero means
+    /// that the visual is unknown.
+    pub fn from_xlib(window: core::ffi::c_ulong, visual_id: core::ffi::c_ulong) -> Self {
+        let mut xlib = raw_window_handle::XlibWindowHandle::new(window);
+        xlib.visual_id = visual_id;
+        Self::from_value(xlib.into())
+    }
 
-        #[cfg(target_family = "wasm")]
-        RawWindowHandle::WebOffscreenCanvas(web) => {
-            use wasm_bindgen::convert::{IntoWasmAbi, RefFromWasmAbi};
+    /// Create a handle to an XCB window from its ID's.
+    ///
+    /// XCB windows are plain numbers, so this is safe and can't fail. This is useful to get
+    /// a handle back after sending its ID's to another process.
+    pub fn from_xcb(
+        window: core::num::NonZeroU32,
+        visual_id: Option<core::num::NonZeroU32>,
+    ) -> Self {
+        let mut xcb = raw_window_handle::XcbWindowHandle::new(window);
+        xcb.visual_id = visual_id;
+        Self::from_value(xcb.into())
+    }
 
-            // Get the underlying canvas.
-            // SAFETY: Guaranteed to be a valid `OffscreenCanvas`.
-            let canvas = unsafe { web_sys::OffscreenCanvas::ref_from_abi(web.obj as _) };
+    /// Create a handle to a Win32 window from its `HWND` and `HINSTANCE`.
+    ///
+    /// Win32 windows are ID's into a thread local table, so this is safe and can't fail. The
+    /// handle is still only meaningful on the thread that created the window.
+    pub fn from_win32(
+        hwnd: core::num::NonZeroIsize,
+        hinstance: Option<core::num::NonZeroIsize>,
+    ) -> Self {
+        let mut win32 = raw_window_handle::Win32WindowHandle::new(hwnd);
+        win32.hinstance = hinstance;
+        Self::from_value(win32.into())
+    }
+
+    /// Create a handle to the canvas whose `attr` attribute is set to `id`.
+    ///
+    /// `Web` window handles are looked up through the `data-raw-handle` attribute. This is
+    /// for frameworks that tag their canvases with a different attribute instead. The
+    /// attribute name is escaped, so it can't change the meaning of the selector.
+    ///
+    /// ```no_run
+    /// use owned_window_handle::OwnedWindowHandle;
+    ///
+    /// // Finds `<canvas data-leptos-canvas="1">`.
+    /// let handle = OwnedWindowHandle::new_web_with_attr(1, "data-leptos-canvas").unwrap();
+    /// assert!(handle.canvas_element().is_some());
+    /// ```
+    #[cfg(all(target_family = "wasm", feature = "web"))]
+    pub fn new_web_with_attr(id: u32, attr: &str) -> Result<Self, Error> {
+        use wasm_bindgen::convert::IntoWasmAbi;
+
+        let canvas = web::find_canvas_with_attr(attr, id)?;
+
+        // This is our own reference to the element, see the `Web` case in `acquire`.
+        let raw = RawWindowHandle::WebCanvas(raw_window_handle::WebCanvasWindowHandle::new(
+            canvas.into_abi() as usize,
+        ));
+
+        // SAFETY: We own a reference to the canvas, which `release` frees again.
+        Ok(Self::from_impl(Impl::Direct(unsafe {
+            WindowHandle::borrow_raw(raw)
+        })))
+    }
+
+    /// Create a handle from a raw handle that's a plain value.
+    fn from_value(raw: RawWindowHandle) -> Self {
+        // SAFETY: Xlib, XCB and Win32 handles are plain numbers and are never dereferenced.
+        Self::from_impl(Impl::Direct(unsafe { WindowHandle::borrow_raw(raw) }))
+    }
+
+    /// Create a fake Xlib window handle for testing.
+    ///
+    /// This doesn't touch any real window. Fake handles must never be passed to real
+    /// windowing or GPU API's.
+    #[cfg(feature = "test-util")]
+    pub fn new_fake_xlib(window: core::ffi::c_ulong) -> Self {
+        Self::from_xlib(window, 0)
+    }
+
+    /// Create a fake XCB window handle for testing.
+    ///
+    /// This doesn't touch any real window. Fake handles must never be passed to real
+    /// windowing or GPU API's.
+    #[cfg(feature = "test-util")]
+    pub fn new_fake_xcb(window: core::num::NonZeroU32) -> Self {
+        Self::from_xcb(window, None)
+    }
+
+    /// Create a fake Win32 window handle for testing.
+    ///
+    /// This doesn't touch any real window. Fake handles must never be passed to real
+    /// windowing or GPU API's.
+    #[cfg(feature = "test-util")]
+    pub fn new_fake_win32(hwnd: core::num::NonZeroIsize) -> Self {
+        Self::from_win32(hwnd, None)
+    }
+
+    /// Create a new [`OwnedWindowHandle`] that copies `handle` without any platform logic.
+    ///
+    /// This bypasses everything this crate does to keep windows alive. The handle is copied
+    /// as is, it is never refcounted and nothing is released when it is dropped. This works
+    /// for handles of any platform, including ones this crate doesn't know about.
+    ///
+    /// This is only meant for handles that the caller knows to be plain values, in niche or
+    /// embedded environments this crate doesn't support yet. Prefer
+    /// [`OwnedWindowHandle::from_window_handle`] wherever possible.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be safe to copy and use for as long as the returned handle or any of its
+    /// clones are alive, without acquiring or releasing a reference to it.
+    pub unsafe fn assume_direct(handle: WindowHandle<'_>) -> Self {
+        // SAFETY: The caller guarantees that this handle stays valid.
+        let handle = unsafe { WindowHandle::borrow_raw(handle.as_raw()) };
+        Self::from_impl(Impl::Assumed(handle))
+    }
+
+    /// Take ownership of a raw window handle whose reference count was already incremented.
+    ///
+    /// Unlike [`OwnedWindowHandle::new`], this does not acquire a new reference to the window.
+    /// Instead, the reference held by the caller is transferred to the returned handle, which
+    /// releases it on drop. This is the inverse of [`OwnedWindowHandle::into_raw`].
+    ///
+    /// Kinds of window handles that this build can't release are rejected with an error of
+    /// kind [`ErrorKind::Unsupported`].
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid window handle whose reference count has already been
+    /// incremented exactly once on behalf of the returned handle, in the same way
+    /// [`OwnedWindowHandle::new`] would have done it. For example, for an AppKit view the
+    /// caller must have sent it a `retain` message that it has not balanced yet.
+    pub unsafe fn from_raw(handle: RawWindowHandle) -> Result<Self, Error> {
+        let kind = RawKind::of(&handle);
+        if !is_platform_supported(kind) {
+            return Err(Error(Repr::UnsupportedKind(kind)));
+        }
+
+        let imp = match handle {
+            RawWindowHandle::Wayland(wayland) => {
+                // Wayland windows are tracked by wayland-backend rather than refcounted.
+                // SAFETY: The caller guarantees that the surface is valid.
+                Impl::Wayland(unsafe { wayland::clone_handle(wayland) }?)
+            }
+
+            RawWindowHandle::Web(_) => {
+                // We only ever own web canvases by object, never by ID.
+                return Err(HandleError::NotSupported.into());
+            }
+
+            // SAFETY: The caller guarantees that this handle is valid and already owned.
+            raw => Impl::Direct(unsafe { WindowHandle::borrow_raw(raw) }),
+        };
+
+        Ok(Self::from_impl(imp))
+    }
+
+    /// Get the raw window handle owned by this handle.
+    ///
+    /// The returned handle is only guaranteed to be valid for as long as this
+    /// [`OwnedWindowHandle`] is alive. It must not be used after `self` is dropped.
+    #[inline]
+    pub fn as_raw(&self) -> RawWindowHandle {
+        match &self.imp {
+            Impl::Direct(handle)
+            | Impl::X11 { window: handle, .. }
+            | Impl::Shared { handle, .. }
+            | Impl::Assumed(handle) => handle.as_raw(),
+            Impl::Wayland(wayland) => RawWindowHandle::Wayland(wayland::as_raw(wayland)),
+        }
+    }
+
+    /// Call `f` with the raw window handle owned by this handle.
+    ///
+    /// This is like [`OwnedWindowHandle::as_raw`], except that the raw handle can't outlive
+    /// the borrow of `self`. This makes it harder to accidentally keep using a pointer, such
+    /// as a Wayland surface, after this handle is dropped.
+    #[inline]
+    pub fn with_raw<R>(&self, f: impl FnOnce(&RawWindowHandle) -> R) -> R {
+        f(&self.as_raw())
+    }
+
+    /// Release ownership of the window handle without releasing its reference.
+    ///
+    /// The returned raw handle still holds the reference that this [`OwnedWindowHandle`]
+    /// held. The caller is now responsible for eventually releasing it, either by hand or by
+    /// passing it back into [`OwnedWindowHandle::from_raw`].
+    ///
+    /// Handles created using [`OwnedWindowHandle::borrowing`], [`OwnedWindowHandle::owning`]
+    /// and [`OwnedWindowHandle::assume_direct`] don't hold a reference that could be handed
+    /// over. For them, `self` is given back as the error.
+    #[inline]
+    pub fn into_raw(self) -> Result<RawWindowHandle, Self> {
+        if let Impl::Shared { .. } | Impl::Assumed(_) = self.imp {
+            return Err(self);
+        }
+
+        // The reference is now owned by the caller.
+        #[cfg(feature = "leak-check")]
+        if matches!(self.imp, Impl::Direct(_)) {
+            leak_check::released();
+        }
+
+        #[cfg(feature = "hooks")]
+        hooks::fire(HookEvent::Dropped(self.raw_window_handle_type()));
+
+        #[cfg(all(debug_assertions, feature = "double-drop-check"))]
+        poison::unregister(self.id);
+
+        let raw = self.as_raw();
+        let mut this = mem::ManuallyDrop::new(self);
+
+        // Drop the fields, but don't run our destructor, which would release the reference.
+        // SAFETY: `this` is never used again.
+        unsafe { ptr::drop_in_place(&mut this.imp) };
+
+        Ok(raw)
+    }
+
+    /// Transform the raw window handle while keeping ownership of it.
+    ///
+    /// `f` receives the current raw handle and returns the one to own instead. The reference
+    /// held by this handle is transferred over rather than acquired again. If `f` changes
+    /// the platform of the handle, an error is returned and this handle is dropped.
+    ///
+    /// # Safety
+    ///
+    /// The handle returned by `f` must be valid and refer to the same underlying object as
+    /// the one passed to it, so that releasing it releases the reference held by `self`.
+    pub unsafe fn map_raw(
+        mut self,
+        f: impl FnOnce(RawWindowHandle) -> RawWindowHandle,
+    ) -> Result<Self, Error> {
+        let raw = self.as_raw();
+        let new = f(raw);
+        if mem::discriminant(&raw) != mem::discriminant(&new) {
+            return Err(Error(Repr::VariantChanged));
+        }
+
+        match (&mut self.imp, new) {
+            (
+                Impl::Direct(handle)
+                | Impl::X11 { window: handle, .. }
+                | Impl::Shared { handle, .. }
+                | Impl::Assumed(handle),
+                new,
+            ) => {
+                // SAFETY: The caller guarantees that the new handle is valid.
+                *handle = unsafe { WindowHandle::borrow_raw(new) };
+            }
+
+            (Impl::Wayland(wayland), RawWindowHandle::Wayland(new)) => {
+                // SAFETY: The caller guarantees that the new surface is valid.
+                *wayland = unsafe { wayland::clone_handle(new) }?;
+            }
+
+            (Impl::Wayland(_), _) => unreachable!("the variant was checked above"),
+        }
+
+        Ok(self)
+    }
+
+    /// Leak this handle, returning a reference to it that lives forever.
+    ///
+    /// This works like [`Box::leak`]. The handle is never dropped, so the reference it holds
+    /// to the window is intentionally never released.
+    #[inline]
+    pub fn leak(self) -> &'static OwnedWindowHandle {
+        Box::leak(Box::new(self))
+    }
+
+    /// Tell whether the underlying window object is still alive.
+    ///
+    /// For Wayland surfaces, this asks `wayland-backend` whether the surface is still
+    /// registered, which is not the case once it was destroyed. Every other platform either
+    /// copies its handles or keeps the window alive through its reference count, so this
+    /// always returns `true` there.
+    #[inline]
+    pub fn is_alive(&self) -> bool {
+        match &self.imp {
+            Impl::Wayland(wayland) => wayland::is_alive(wayland),
+            _ => true,
+        }
+    }
+
+    /// Get the display connection for this X11 window.
+    ///
+    /// This is only available for handles created using [`OwnedWindowHandle::new_x11`].
+    #[inline]
+    pub fn x11_display(&self) -> Option<&OwnedDisplayHandle> {
+        match &self.imp {
+            Impl::X11 { display, .. } => Some(display),
+            _ => None,
+        }
+    }
+
+    /// Tell whether `other` belongs to the same display connection as this handle.
+    ///
+    /// This is supported for two Wayland surfaces, and for two X11 windows created using
+    /// [`OwnedWindowHandle::new_x11`]. `None` is returned for everything else.
+    pub fn same_display(&self, other: &Self) -> Option<bool> {
+        match (&self.imp, &other.imp) {
+            (Impl::Wayland(a), Impl::Wayland(b)) => Some(wayland::same_display(a, b)),
+            (Impl::X11 { display: a, .. }, Impl::X11 { display: b, .. }) => {
+                let a = a.display_handle().ok()?;
+                let b = b.display_handle().ok()?;
+                Some(a.as_raw() == b.as_raw())
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the windowing backend this handle belongs to.
+    #[inline]
+    pub fn platform(&self) -> Platform {
+        match self.as_raw() {
+            RawWindowHandle::Xlib(_) => Platform::Xlib,
+            RawWindowHandle::Xcb(_) => Platform::Xcb,
+            RawWindowHandle::Wayland(_) => Platform::Wayland,
+            RawWindowHandle::Drm(_) => Platform::Drm,
+            RawWindowHandle::Gbm(_) => Platform::Gbm,
+            RawWindowHandle::Win32(_) => Platform::Win32,
+            RawWindowHandle::WinRt(_) => Platform::WinRt,
+            RawWindowHandle::AppKit(_) => Platform::AppKit,
+            RawWindowHandle::UiKit(_) => Platform::UiKit,
+            RawWindowHandle::AndroidNdk(_) => Platform::AndroidNdk,
+            RawWindowHandle::OhosNdk(_) => Platform::OhosNdk,
+            RawWindowHandle::Haiku(_) => Platform::Haiku,
+            RawWindowHandle::Orbital(_) => Platform::Orbital,
+            RawWindowHandle::Web(_)
+            | RawWindowHandle::WebCanvas(_)
+            | RawWindowHandle::WebOffscreenCanvas(_) => Platform::Web,
+            _ => Platform::Other,
+        }
+    }
+
+    /// Get the size of an [`OwnedWindowHandle`] in bytes on this target.
+    ///
+    /// This is meant for code that allocates storage for handles up front, such as C
+    /// allocators. The size depends on the target, on the enabled features and on whether
+    /// debug assertions are enabled, so it should never be hardcoded.
+    #[inline]
+    pub const fn size_hint() -> usize {
+        mem::size_of::<Self>()
+    }
+
+    /// Get the alignment of an [`OwnedWindowHandle`] in bytes on this target.
+    ///
+    /// See [`OwnedWindowHandle::size_hint`].
+    #[inline]
+    pub const fn align_hint() -> usize {
+        mem::align_of::<Self>()
+    }
+
+    /// Borrow the window handle owned by this handle.
+    ///
+    /// This does the same as [`HasWindowHandle::window_handle`], without needing the trait in
+    /// scope. It also returns this crate's [`Error`], which tells why the window handle is
+    /// unavailable. For instance, a destroyed Wayland surface gives an error of kind
+    /// [`ErrorKind::WaylandDestroyed`].
+    #[inline]
+    pub fn handle(&self) -> Result<WindowHandle<'_>, Error> {
+        match &self.imp {
+            Impl::Direct(handle)
+            | Impl::X11 { window: handle, .. }
+            | Impl::Shared { handle, .. }
+            | Impl::Assumed(handle) => Ok(*handle),
+            Impl::Wayland(wayland) => wayland::as_ptr(wayland).map_err(|err| match err {
+                HandleError::Unavailable => Error(Repr::WaylandDestroyed),
+                err => err.into(),
+            }),
+        }
+    }
+
+    /// Get the variant of the raw window handle owned by this handle.
+    #[inline]
+    pub fn raw_window_handle_type(&self) -> RawKind {
+        RawKind::of(&self.as_raw())
+    }
+
+    /// Get a number that identifies the window behind this handle.
+    ///
+    /// This is the window ID for Xlib, XCB, Win32 and DRM windows, and the address of the
+    /// window object for everything else. Handles to the same window have the same identity,
+    /// except for web canvases, where every handle owns a separate JavaScript reference with
+    /// its own identity.
+    ///
+    /// The identity is only unique within one process. It must not be sent to other
+    /// processes or persisted. Use [`Hash`] instead if collisions between different kinds of
+    /// windows matter.
+    #[inline]
+    pub fn identity(&self) -> usize {
+        let raw = self.as_raw();
+        match raw {
+            RawWindowHandle::WebCanvas(web) => web.obj,
+            RawWindowHandle::WebOffscreenCanvas(web) => web.obj,
+            raw => window_id(&raw).unwrap_or(0),
+        }
+    }
+
+    /// Tell whether `other` refers to the same window as this handle.
+    ///
+    /// Unlike comparing against a second [`OwnedWindowHandle`], this does not acquire a
+    /// reference to `other`. An error is only returned if `other` fails to produce a window
+    /// handle.
+    #[inline]
+    pub fn is_same_window(&self, other: impl HasWindowHandle) -> Result<bool, Error> {
+        let other = other.window_handle()?;
+        Ok(same_window(&self.as_raw(), &other.as_raw()))
+    }
+
+    /// Tell whether a [`winit`] window is the window this handle refers to.
+    ///
+    /// This is [`OwnedWindowHandle::is_same_window`] for [`winit`] windows, which is useful to
+    /// find the handle a window event belongs to.
+    ///
+    /// [`winit`]: https://crates.io/crates/winit
+    #[cfg(feature = "winit")]
+    #[inline]
+    pub fn matches_window(&self, window: &winit::window::Window) -> Result<bool, Error> {
+        self.is_same_window(window)
+    }
+
+    /// Get the Win32 window handle, if this is a Win32 window.
+    #[cfg(windows)]
+    #[inline]
+    pub fn as_win32(&self) -> Option<raw_window_handle::Win32WindowHandle> {
+        match self.as_raw() {
+            RawWindowHandle::Win32(win32) => Some(win32),
+            _ => None,
+        }
+    }
+
+    /// Get the `HINSTANCE` of this Win32 window, if the handle carries one.
+    ///
+    /// Returns `None` if this is not a Win32 window, or if the window handle was created
+    /// without an `HINSTANCE`. A missing instance can be recovered from the window itself
+    /// using `GetWindowLongPtr(hwnd, GWLP_HINSTANCE)`, which is what
+    /// [`OwnedWindowHandle::win32_hinstance_or_query`] does.
+    #[cfg(windows)]
+    #[inline]
+    pub fn win32_hinstance(&self) -> Option<isize> {
+        self.as_win32()?.hinstance.map(|hinstance| hinstance.get())
+    }
+
+    /// Get the `HINSTANCE` of this Win32 window, asking the window for it if the handle
+    /// doesn't carry one.
+    ///
+    /// Returns `None` if this is not a Win32 window, or if the window has no instance.
+    #[cfg(windows)]
+    pub fn win32_hinstance_or_query(&self) -> Option<isize> {
+        let win32 = self.as_win32()?;
+        if let Some(hinstance) = win32.hinstance {
+            return Some(hinstance.get());
+        }
+
+        // SAFETY: We hold a valid window handle, and `GetWindowLongPtr` fails gracefully for
+        // windows that were destroyed in the meantime.
+        let hinstance = unsafe { win32::window_instance(win32.hwnd.get()) };
+        if hinstance == 0 {
+            None
+        } else {
+            Some(hinstance)
+        }
+    }
+
+    /// Get the Xlib window handle, if this is an Xlib window.
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "redox",
+            target_family = "wasm",
+            target_os = "android",
+            target_vendor = "apple"
+        ))
+    ))]
+    #[inline]
+    pub fn as_xlib(&self) -> Option<raw_window_handle::XlibWindowHandle> {
+        match self.as_raw() {
+            RawWindowHandle::Xlib(xlib) => Some(xlib),
+            _ => None,
+        }
+    }
+
+    /// Get the XCB window handle, if this is an XCB window.
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "redox",
+            target_family = "wasm",
+            target_os = "android",
+            target_vendor = "apple"
+        ))
+    ))]
+    #[inline]
+    pub fn as_xcb(&self) -> Option<raw_window_handle::XcbWindowHandle> {
+        match self.as_raw() {
+            RawWindowHandle::Xcb(xcb) => Some(xcb),
+            _ => None,
+        }
+    }
+
+    /// Get the visual ID of this XCB window, if it has one.
+    ///
+    /// Returns `None` if this is not an XCB window, or if the handle was created without a
+    /// visual ID.
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "redox",
+            target_family = "wasm",
+            target_os = "android",
+            target_vendor = "apple"
+        ))
+    ))]
+    #[inline]
+    pub fn xcb_visual_id(&self) -> Option<u32> {
+        match self.as_raw() {
+            RawWindowHandle::Xcb(xcb) => xcb.visual_id.map(|id| id.get()),
+            _ => None,
+        }
+    }
+
+    /// Set the visual ID of this XCB window.
+    ///
+    /// This is useful if the window and its visual come from different libraries. The
+    /// window is only copied, no reference to it is acquired. Returns an error of kind
+    /// [`ErrorKind::PlatformMismatch`] if this is not an XCB window.
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "redox",
+            target_family = "wasm",
+            target_os = "android",
+            target_vendor = "apple"
+        ))
+    ))]
+    pub fn with_xcb_visual_id(self, visual_id: core::num::NonZeroU32) -> Result<Self, Error> {
+        if !matches!(self.as_raw(), RawWindowHandle::Xcb(_)) {
+            return Err(Error(Repr::PlatformMismatch { expected: "xcb" }));
+        }
+
+        // SAFETY: XCB windows are just numeric ID's, and the window stays the same.
+        unsafe {
+            self.map_raw(|raw| match raw {
+                RawWindowHandle::Xcb(mut xcb) => {
+                    xcb.visual_id = Some(visual_id);
+                    RawWindowHandle::Xcb(xcb)
+                }
+                raw => raw,
+            })
+        }
+    }
+
+    /// Get the retain count of the AppKit or UIKit view behind this handle.
+    ///
+    /// `retainCount` is unreliable for reasoning about absolute numbers, since the system
+    /// can retain views for its own purposes. It is still useful for debugging leaks by
+    /// comparing counts over time. Returns `None` if this is not an Apple view, or if this
+    /// isn't called from the main thread.
+    #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+    pub fn apple_retain_count(&self) -> Option<usize> {
+        let view = match self.as_raw() {
+            RawWindowHandle::AppKit(appkit) => appkit.ns_view,
+            RawWindowHandle::UiKit(uikit) => uikit.ui_view,
+            _ => return None,
+        };
+
+        if !apple::is_main_thread() {
+            return None;
+        }
+
+        // SAFETY: We hold a reference to the view, so it is valid.
+        Some(unsafe { apple::retain_count(view) })
+    }
+
+    /// Get an XCB handle to the same window as this X11 window.
+    ///
+    /// Xlib and XCB window ID's are the same number, so this just copies the window and
+    /// visual ID's over. Returns `Ok(None)` if this is not an X11 window, or if the Xlib
+    /// window ID doesn't fit into an XCB window ID.
+    ///
+    /// An Xlib display can't be used with XCB, so an Xlib handle created using
+    /// [`OwnedWindowHandle::new_x11`] loses its display along the way. An XCB handle is just
+    /// cloned, including its display. An error is only returned if cloning the display fails.
+    pub fn to_xcb(&self) -> Result<Option<OwnedWindowHandle>, Error> {
+        let xlib = match self.as_raw() {
+            RawWindowHandle::Xlib(xlib) => xlib,
+            RawWindowHandle::Xcb(_) => return self.try_clone().map(Some),
+            _ => return Ok(None),
+        };
+
+        let window = match u32::try_from(xlib.window)
+            .ok()
+            .and_then(core::num::NonZeroU32::new)
+        {
+            Some(window) => window,
+            None => return Ok(None),
+        };
+        let visual_id = u32::try_from(xlib.visual_id)
+            .ok()
+            .and_then(core::num::NonZeroU32::new);
+
+        Ok(Some(Self::from_xcb(window, visual_id)))
+    }
+
+    /// Get an Xlib handle to the same window as this X11 window.
+    ///
+    /// Xlib and XCB window ID's are the same number, so this just copies the window and
+    /// visual ID's over. Returns `Ok(None)` if this is not an X11 window.
+    ///
+    /// An XCB connection can't be used with Xlib, so an XCB handle created using
+    /// [`OwnedWindowHandle::new_x11`] loses its display along the way. An Xlib handle is just
+    /// cloned, including its display. An error is only returned if cloning the display fails.
+    pub fn to_xlib(&self) -> Result<Option<OwnedWindowHandle>, Error> {
+        let xcb = match self.as_raw() {
+            RawWindowHandle::Xcb(xcb) => xcb,
+            RawWindowHandle::Xlib(_) => return self.try_clone().map(Some),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Self::from_xlib(
+            xcb.window.get().into(),
+            xcb.visual_id.map_or(0, |id| id.get().into()),
+        )))
+    }
+
+    /// Get the AppKit window handle, if this is an AppKit view.
+    ///
+    /// The view is retained for as long as this handle is alive.
+    #[cfg(target_os = "macos")]
+    #[inline]
+    pub fn as_appkit(&self) -> Option<raw_window_handle::AppKitWindowHandle> {
+        match self.as_raw() {
+            RawWindowHandle::AppKit(appkit) => Some(appkit),
+            _ => None,
+        }
+    }
+
+    /// Get the canvas element behind this handle, if this is a web canvas.
+    ///
+    /// This clones the JavaScript reference to the canvas, so the returned element is owned
+    /// separately from this handle. Returns `None` for offscreen canvases and every other
+    /// kind of window.
+    #[cfg(all(target_family = "wasm", feature = "web"))]
+    pub fn canvas_element(&self) -> Option<web_sys::HtmlCanvasElement> {
+        use wasm_bindgen::convert::RefFromWasmAbi;
+
+        match self.as_raw() {
+            RawWindowHandle::WebCanvas(web) => {
+                // SAFETY: We own a reference to this `HtmlCanvasElement`, see `acquire`.
+                let canvas = unsafe { web_sys::HtmlCanvasElement::ref_from_abi(web.obj as _) };
+                Some((&*canvas).clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the Wayland window handle, if this is a Wayland surface.
+    #[cfg(all(
+        feature = "wayland",
+        unix,
+        not(any(
+            target_os = "redox",
+            target_family = "wasm",
+            target_os = "android",
+            target_vendor = "apple"
+        ))
+    ))]
+    #[inline]
+    pub fn as_wayland(&self) -> Option<raw_window_handle::WaylandWindowHandle> {
+        match &self.imp {
+            Impl::Wayland(wayland) => Some(wayland::as_raw(wayland)),
+            _ => None,
+        }
+    }
+
+    /// Get the `wl_display` pointer of the Wayland surface, if this is a Wayland surface.
+    ///
+    /// This is the display the surface was created on, as needed by EGL to create a window
+    /// surface. It stays valid for as long as this handle is alive.
+    #[cfg(all(
+        feature = "wayland",
+        unix,
+        not(any(
+            target_os = "redox",
+            target_family = "wasm",
+            target_os = "android",
+            target_vendor = "apple"
+        ))
+    ))]
+    #[inline]
+    pub fn wayland_display_ptr(&self) -> Option<ptr::NonNull<core::ffi::c_void>> {
+        match &self.imp {
+            Impl::Wayland(wayland) => wayland::display_ptr(wayland),
+            _ => None,
+        }
+    }
+
+    /// Get the `wayland-backend` object ID of the surface, if this is a Wayland surface.
+    ///
+    /// This can be used to construct a typed `WlSurface` proxy using `wayland-client`.
+    #[cfg(all(
+        feature = "wayland",
+        unix,
+        not(any(
+            target_os = "redox",
+            target_family = "wasm",
+            target_os = "android",
+            target_vendor = "apple"
+        ))
+    ))]
+    #[inline]
+    pub fn wayland_object_id(&self) -> Option<wayland_backend::sys::client::ObjectId> {
+        match &self.imp {
+            Impl::Wayland(wayland) => Some(wayland::object_id(wayland).clone()),
+            _ => None,
+        }
+    }
+
+    /// Get the `wayland-backend` backend the surface belongs to, if this is a Wayland surface.
+    #[cfg(all(
+        feature = "wayland",
+        unix,
+        not(any(
+            target_os = "redox",
+            target_family = "wasm",
+            target_os = "android",
+            target_vendor = "apple"
+        ))
+    ))]
+    #[inline]
+    pub fn wayland_backend(&self) -> Option<wayland_backend::sys::client::Backend> {
+        match &self.imp {
+            Impl::Wayland(wayland) => Some(wayland::backend(wayland).clone()),
+            _ => None,
+        }
+    }
+
+    /// Clone this window handle.
+    #[inline]
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        match &self.imp {
+            Impl::Direct(handle) => {
+                // Just increment refcount on the handle.
+                Self::from_window_handle(*handle)
+            }
+
+            Impl::Wayland(wayland) => {
+                // wayland-backend's objects can be cheaply cloned.
+                Ok(Self::from_impl(Impl::Wayland(wayland.clone())))
+            }
+
+            Impl::X11 { window, display } => {
+                // X11 windows are just copied, but the display needs to be cloned.
+                Ok(Self::from_impl(Impl::X11 {
+                    window: *window,
+                    display: display.try_clone()?,
+                }))
+            }
+
+            Impl::Assumed(handle) => {
+                // The caller promised that this handle can just be copied.
+                Ok(Self::from_impl(Impl::Assumed(*handle)))
+            }
+
+            Impl::Shared { handle, window } => {
+                // The shared pointer keeps the window alive.
+                Ok(Self::from_impl(Impl::Shared {
+                    handle: *handle,
+                    window: window.clone(),
+                }))
+            }
+        }
+    }
+
+    /// Clone this window handle into `dst`, replacing its previous contents.
+    ///
+    /// If `dst` already holds a reference to the same window, that reference is kept instead
+    /// of acquiring a new one and releasing the old one, and Wayland surfaces are cloned in
+    /// place. Otherwise, the reference held by `dst` is only released once the new clone has
+    /// been acquired. If cloning fails, `dst` is left untouched.
+    pub fn try_clone_into(&self, dst: &mut OwnedWindowHandle) -> Result<(), Error> {
+        match (&self.imp, &mut dst.imp) {
+            (Impl::Direct(src), Impl::Direct(dst)) | (Impl::Assumed(src), Impl::Assumed(dst))
+                if src.as_raw() == dst.as_raw() =>
+            {
+                // `dst` already owns exactly this handle.
+                return Ok(());
+            }
+
+            (
+                Impl::Shared { handle, window },
+                Impl::Shared {
+                    handle: dst_handle,
+                    window: dst_window,
+                },
+            ) if Arc::ptr_eq(window, dst_window) => {
+                // The shared pointer already keeps the window alive.
+                *dst_handle = *handle;
+                return Ok(());
+            }
+
+            (Impl::Wayland(src), Impl::Wayland(dst)) => {
+                // wayland-backend's objects can be cloned in place.
+                dst.clone_from(src);
+                return Ok(());
+            }
+
+            _ => {}
+        }
+
+        *dst = self.try_clone()?;
+        Ok(())
+    }
+
+    /// Replace the window owned by this handle with `new`.
+    ///
+    /// The new window is acquired before the old one is released. If acquiring it fails,
+    /// `self` is left untouched.
+    pub fn replace<W: HasWindowHandle>(&mut self, new: &W) -> Result<(), Error> {
+        *self = Self::new(new)?;
+        Ok(())
+    }
+
+    /// Push `n` clones of this window handle onto `dst`.
+    ///
+    /// This reuses the capacity of `dst`, which is useful for keeping a pool of handles. If
+    /// any of the clones fails to be created, `dst` is truncated back to its original length,
+    /// releasing the clones pushed so far. Handles that were already in `dst` are kept.
+    pub fn try_clone_many_into(&self, dst: &mut Vec<Self>, n: usize) -> Result<(), Error> {
+        let len = dst.len();
+        dst.reserve(n);
+
+        for _ in 0..n {
+            match self.try_clone() {
+                Ok(clone) => dst.push(clone),
+                Err(err) => {
+                    dst.truncate(len);
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create `N` clones of this window handle at once.
+    ///
+    /// If any of the clones fails to be created, the ones created so far are released.
+    pub fn try_clone_array<const N: usize>(&self) -> Result<[OwnedWindowHandle; N], Error> {
+        /// Releases the clones created so far if cloning fails.
+        struct Guard<'a, const N: usize> {
+            clones: &'a mut [mem::MaybeUninit<OwnedWindowHandle>; N],
+            initialized: usize,
+        }
+
+        impl<const N: usize> Drop for Guard<'_, N> {
+            fn drop(&mut self) {
+                for clone in &mut self.clones[..self.initialized] {
+                    // SAFETY: The first `initialized` clones were written.
+                    unsafe { clone.assume_init_drop() };
+                }
+            }
+        }
+
+        // SAFETY: An array of `MaybeUninit` doesn't need to be initialized.
+        let mut clones: [mem::MaybeUninit<OwnedWindowHandle>; N] =
+            unsafe { mem::MaybeUninit::uninit().assume_init() };
+        let mut guard = Guard {
+            clones: &mut clones,
+            initialized: 0,
+        };
+
+        while guard.initialized < N {
+            guard.clones[guard.initialized].write(self.try_clone()?);
+            guard.initialized += 1;
+        }
+
+        mem::forget(guard);
+
+        // SAFETY: Every clone was written, and `MaybeUninit<T>` has the same layout as `T`.
+        Ok(unsafe { ptr::read(clones.as_ptr().cast::<[OwnedWindowHandle; N]>()) })
+    }
+
+    /// Bundle this window with the display it belongs to.
+    ///
+    /// See [`OwnedWindowDisplayHandle`].
+    #[inline]
+    pub fn with_display(self, display: OwnedDisplayHandle) -> OwnedWindowDisplayHandle {
+        OwnedWindowDisplayHandle::new(self, display)
+    }
+
+    /// Create a weak reference to this window handle.
+    ///
+    /// See [`WeakWindowHandle`] for which windows can be upgraded again.
+    #[inline]
+    pub fn downgrade(&self) -> WeakWindowHandle {
+        WeakWindowHandle::new(self)
+    }
+
+    /// Create a weak reference to a clone of this window handle.
+    ///
+    /// A weak reference doesn't keep the window alive, so this is the same as
+    /// [`OwnedWindowHandle::downgrade`]. No strong clone is ever created, which means no
+    /// reference to the window is acquired and released along the way. This currently never
+    /// fails, but may on platforms where weak references need to be registered.
+    #[inline]
+    pub fn try_clone_weak(&self) -> Result<WeakWindowHandle, Error> {
+        Ok(self.downgrade())
+    }
+}
+
+impl Clone for OwnedWindowHandle {
+    /// Clone this window handle.
+    ///
+    /// On most platforms cloning cannot fail. See [`OwnedWindowHandle::try_clone`] for a
+    /// fallible version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the platform fails to acquire another reference to the window, e.g. if
+    /// retaining an Apple view fails.
+    #[inline]
+    fn clone(&self) -> Self {
+        self.try_clone().expect("failed to clone window handle")
+    }
+
+    #[inline]
+    fn clone_from(&mut self, source: &Self) {
+        source
+            .try_clone_into(self)
+            .expect("failed to clone window handle")
+    }
+}
+
+impl Drop for OwnedWindowHandle {
+    fn drop(&mut self) {
+        // Catch bitwise copies before they release the window a second time.
+        #[cfg(all(debug_assertions, feature = "double-drop-check"))]
+        poison::unregister(self.id);
+
+        #[cfg(feature = "hooks")]
+        hooks::fire(HookEvent::Dropped(self.raw_window_handle_type()));
+
+        if let Impl::Direct(handle) = self.imp {
+            // SAFETY: Our handle was created via inc_refcount.
+            let _result = unsafe { dec_refcount(handle) };
+
+            // Views dropped off the main thread leak their reference, as documented.
+            #[cfg(debug_assertions)]
+            if let Err(err) = _result {
+                if err.0 != Repr::NotMainThread {
+                    panic!("failed to release window: {}", err);
+                }
+            }
+        }
+    }
+}
+
+impl PartialEq for OwnedWindowHandle {
+    /// Tell whether two handles refer to the same window.
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.imp, &other.imp) {
+            (
+                Impl::Direct(a)
+                | Impl::X11 { window: a, .. }
+                | Impl::Shared { handle: a, .. }
+                | Impl::Assumed(a),
+                Impl::Direct(b)
+                | Impl::X11 { window: b, .. }
+                | Impl::Shared { handle: b, .. }
+                | Impl::Assumed(b),
+            ) => same_window(&a.as_raw(), &b.as_raw()),
+            (Impl::Wayland(a), Impl::Wayland(b)) => wayland::same_surface(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for OwnedWindowHandle {}
+
+impl Hash for OwnedWindowHandle {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match &self.imp {
+            Impl::Direct(handle)
+            | Impl::X11 { window: handle, .. }
+            | Impl::Shared { handle, .. }
+            | Impl::Assumed(handle) => hash_window(&handle.as_raw(), state),
+            Impl::Wayland(wayland) => wayland::hash_surface(wayland, state),
+        }
+    }
+}
+
+impl HasWindowHandle for OwnedWindowHandle {
+    #[inline]
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        self.handle().map_err(|err| match err.0 {
+            Repr::Raw(err) => err,
+            _ => HandleError::Unavailable,
+        })
+    }
+}
+
+/// Take ownership of several windows at once.
+///
+/// This stops at the first window that fails, releasing every window owned so far.
+pub fn own_all<'a, I, W>(windows: I) -> Result<Vec<OwnedWindowHandle>, Error>
+where
+    I: IntoIterator<Item = &'a W>,
+    W: HasWindowHandle + 'a,
+{
+    windows.into_iter().map(OwnedWindowHandle::new).collect()
+}
+
+/// Take ownership of a numeric window handle and release it again.
+///
+/// This runs `raw` through the same logic as [`OwnedWindowHandle::new`] and returns the
+/// raw handle that was owned. Only Xlib, XCB, Win32 and DRM handles are accepted, since they
+/// are plain values that never point to real objects. This makes it suitable for fuzzing.
+/// Every other kind of handle is rejected with an error of kind [`ErrorKind::Unsupported`].
+#[cfg(feature = "test-util")]
+pub fn roundtrip_raw(raw: RawWindowHandle) -> Result<RawWindowHandle, Error> {
+    match raw {
+        RawWindowHandle::Xlib(_)
+        | RawWindowHandle::Xcb(_)
+        | RawWindowHandle::Win32(_)
+        | RawWindowHandle::Drm(_) => {}
+        _ => return Err(HandleError::NotSupported.into()),
+    }
+
+    // SAFETY: The handles above are plain values that are never dereferenced.
+    let handle = unsafe { WindowHandle::borrow_raw(raw) };
+    let owned = OwnedWindowHandle::from_window_handle(handle)?;
+    Ok(owned.as_raw())
+}
+
+/// Get the number of window references currently held by [`OwnedWindowHandle`]s.
+///
+/// This counts every handle that releases a reference to its window when it is dropped.
+/// Once all handles are dropped, this should return to zero; anything else means a
+/// reference was leaked. Handles passed to [`OwnedWindowHandle::into_raw`] are no longer
+/// counted, and handles passed to [`OwnedWindowHandle::leak`] are counted forever. A
+/// reference that fails to be released, such as a view dropped off the main thread, stays
+/// counted as well.
+#[cfg(feature = "leak-check")]
+#[inline]
+pub fn outstanding_handles() -> usize {
+    leak_check::outstanding()
+}
+
+/// The windowing backend behind an [`OwnedWindowHandle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Platform {
+    /// The X Window System, through Xlib.
+    Xlib,
+
+    /// The X Window System, through XCB.
+    Xcb,
+
+    /// Wayland.
+    Wayland,
+
+    /// The Linux Direct Rendering Manager.
+    Drm,
+
+    /// The Generic Buffer Manager.
+    Gbm,
+
+    /// Win32.
+    Win32,
+
+    /// The Windows Runtime.
+    WinRt,
+
+    /// AppKit, used on macOS.
+    AppKit,
+
+    /// UIKit, used on iOS, tvOS and visionOS.
+    UiKit,
+
+    /// The Android NDK.
+    AndroidNdk,
+
+    /// The OpenHarmony NDK.
+    OhosNdk,
+
+    /// Haiku.
+    Haiku,
+
+    /// Orbital, used on Redox.
+    Orbital,
+
+    /// The web, including offscreen canvases.
+    Web,
+
+    /// A platform this version of the crate doesn't know about.
+    Other,
+}
+
+/// The variant of a [`RawWindowHandle`].
+///
+/// Unlike [`Platform`], this maps one to one onto the variants of [`RawWindowHandle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RawKind {
+    /// [`RawWindowHandle::Xlib`].
+    Xlib,
+
+    /// [`RawWindowHandle::Xcb`].
+    Xcb,
+
+    /// [`RawWindowHandle::Wayland`].
+    Wayland,
+
+    /// [`RawWindowHandle::Drm`].
+    Drm,
+
+    /// [`RawWindowHandle::Gbm`].
+    Gbm,
+
+    /// [`RawWindowHandle::Win32`].
+    Win32,
+
+    /// [`RawWindowHandle::WinRt`].
+    WinRt,
+
+    /// [`RawWindowHandle::AppKit`].
+    AppKit,
+
+    /// [`RawWindowHandle::UiKit`].
+    UiKit,
+
+    /// [`RawWindowHandle::AndroidNdk`].
+    AndroidNdk,
+
+    /// [`RawWindowHandle::OhosNdk`].
+    OhosNdk,
+
+    /// [`RawWindowHandle::Haiku`].
+    Haiku,
+
+    /// [`RawWindowHandle::Orbital`].
+    Orbital,
+
+    /// [`RawWindowHandle::Web`].
+    Web,
+
+    /// [`RawWindowHandle::WebCanvas`].
+    WebCanvas,
+
+    /// [`RawWindowHandle::WebOffscreenCanvas`].
+    WebOffscreenCanvas,
+
+    /// A variant this version of the crate doesn't know about.
+    Other,
+}
+
+impl RawKind {
+    /// Get the variant of a raw window handle.
+    #[inline]
+    pub fn of(raw: &RawWindowHandle) -> Self {
+        match raw {
+            RawWindowHandle::Xlib(_) => Self::Xlib,
+            RawWindowHandle::Xcb(_) => Self::Xcb,
+            RawWindowHandle::Wayland(_) => Self::Wayland,
+            RawWindowHandle::Drm(_) => Self::Drm,
+            RawWindowHandle::Gbm(_) => Self::Gbm,
+            RawWindowHandle::Win32(_) => Self::Win32,
+            RawWindowHandle::WinRt(_) => Self::WinRt,
+            RawWindowHandle::AppKit(_) => Self::AppKit,
+            RawWindowHandle::UiKit(_) => Self::UiKit,
+            RawWindowHandle::AndroidNdk(_) => Self::AndroidNdk,
+            RawWindowHandle::OhosNdk(_) => Self::OhosNdk,
+            RawWindowHandle::Haiku(_) => Self::Haiku,
+            RawWindowHandle::Orbital(_) => Self::Orbital,
+            RawWindowHandle::Web(_) => Self::Web,
+            RawWindowHandle::WebCanvas(_) => Self::WebCanvas,
+            RawWindowHandle::WebOffscreenCanvas(_) => Self::WebOffscreenCanvas,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Tell whether this build of the crate can take ownership of a kind of window handle.
+///
+/// This only depends on the target and the enabled features, so it can be used to branch
+/// before a handle is ever created. Handles of a supported kind can still fail at runtime,
+/// for instance because the window is invalid.
+pub const fn is_platform_supported(kind: RawKind) -> bool {
+    match kind {
+        RawKind::Xlib | RawKind::Xcb | RawKind::Win32 => true,
+        RawKind::Drm | RawKind::Gbm => cfg!(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )),
+        RawKind::Wayland => cfg!(all(
+            feature = "wayland",
+            unix,
+            not(any(
+                target_os = "redox",
+                target_family = "wasm",
+                target_os = "android",
+                target_vendor = "apple"
+            ))
+        )),
+        RawKind::WinRt => cfg!(windows),
+        RawKind::AppKit | RawKind::UiKit => cfg!(all(target_vendor = "apple", feature = "appkit")),
+        RawKind::AndroidNdk => cfg!(all(target_os = "android", feature = "android")),
+        RawKind::OhosNdk => cfg!(target_env = "ohos"),
+        RawKind::Haiku => cfg!(target_os = "haiku"),
+        RawKind::Orbital => cfg!(target_os = "redox"),
+        RawKind::Web | RawKind::WebCanvas | RawKind::WebOffscreenCanvas => {
+            cfg!(all(target_family = "wasm", feature = "web"))
+        }
+        RawKind::Other => false,
+    }
+}
+
+// Make sure `is_platform_supported` agrees with the platform arms in `acquire`.
+#[cfg(target_os = "linux")]
+const _: () = assert!(!is_platform_supported(RawKind::AppKit));
+
+#[cfg(all(target_os = "macos", feature = "appkit"))]
+const _: () = assert!(is_platform_supported(RawKind::AppKit));
+
+#[cfg(any(windows, target_vendor = "apple"))]
+const _: () = assert!(!is_platform_supported(RawKind::Drm));
+
+/// Error type for window handles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error(Repr);
+
+impl Error {
+    /// Get the kind of error that occurred.
+    #[inline]
+    pub fn kind(&self) -> ErrorKind {
+        match &self.0 {
+            Repr::Raw(HandleError::NotSupported) => ErrorKind::Unsupported,
+            Repr::Raw(HandleError::Unavailable) => ErrorKind::Unavailable,
+            Repr::Raw(_) => ErrorKind::Other,
+            Repr::PlatformMismatch { .. } => ErrorKind::PlatformMismatch,
+            Repr::NoWindow => ErrorKind::NoWindow,
+            Repr::NoDocument => ErrorKind::NoDocument,
+            Repr::CanvasNotFound(_) => ErrorKind::CanvasNotFound,
+            Repr::RetainFailed(_) => ErrorKind::RetainFailed,
+            Repr::WaylandNotEnabled => ErrorKind::WaylandNotEnabled,
+            Repr::WaylandNotRust => ErrorKind::WaylandNotRust,
+            Repr::NotMainThread => ErrorKind::NotMainThread,
+            Repr::InvalidSelector => ErrorKind::InvalidSelector,
+            Repr::VariantChanged => ErrorKind::PlatformMismatch,
+            Repr::InvalidPointer => ErrorKind::InvalidPointer,
+            Repr::WaylandWrongInterface => ErrorKind::WaylandWrongInterface,
+            Repr::WaylandDestroyed => ErrorKind::WaylandDestroyed,
+            Repr::InvalidHandle => ErrorKind::InvalidHandle,
+            Repr::UnsupportedKind(_) => ErrorKind::Unsupported,
+        }
+    }
+
+    /// Tell whether this error was caused by a Wayland surface that isn't managed by Rust's
+    /// `wayland-backend`.
+    ///
+    /// This happens when the surface comes from an application that uses `libwayland`
+    /// directly, such as SDL or GTK. In that case, the raw window handle can still be used
+    /// directly, as long as the caller keeps the surface alive.
+    #[inline]
+    pub fn is_wayland_foreign(&self) -> bool {
+        matches!(self.0, Repr::WaylandNotRust)
+    }
+}
+
+/// The kind of an [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The window handle is not supported by this crate.
+    Unsupported,
+
+    /// The window handle is currently unavailable.
+    Unavailable,
+
+    /// The window handle is for a different platform than the one we are running on.
+    PlatformMismatch,
+
+    /// There is no `window` on web.
+    ///
+    /// This happens in web workers. Use an offscreen canvas there instead.
+    NoWindow,
+
+    /// The `window` on web has no document.
+    NoDocument,
+
+    /// The canvas referred to by a web handle could not be found.
+    CanvasNotFound,
+
+    /// Retaining the underlying window object failed.
+    RetainFailed,
+
+    /// A Wayland handle was passed, but Wayland support is not enabled.
+    WaylandNotEnabled,
+
+    /// The Wayland surface was not created by Rust's `wayland-backend`.
+    WaylandNotRust,
+
+    /// The window can only be used on the main thread.
+    NotMainThread,
+
+    /// The selector used to find a web canvas was invalid.
+    InvalidSelector,
+
+    /// The window pointer is invalid.
+    InvalidPointer,
+
+    /// The Wayland proxy is not a `wl_surface`.
+    WaylandWrongInterface,
+
+    /// The Wayland surface was already destroyed.
+    WaylandDestroyed,
+
+    /// The window handle does not refer to a valid window.
+    InvalidHandle,
+
+    /// Some other error occurred.
+    Other,
+}
+
+impl From<HandleError> for Error {
+    #[inline]
+    fn from(err: HandleError) -> Self {
+        Self(Repr::Raw(err))
+    }
+}
+
+impl fmt::Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Raw(HandleError::NotSupported) => write!(f, "unsupported platform"),
+            Repr::Raw(HandleError::Unavailable) => write!(f, "window handle is unavailable"),
+            Repr::Raw(_) => write!(f, "unknown raw window handle error"),
+            Repr::CanvasNotFound(id) => write!(f, "canvas not found with id: {}", id),
+            Repr::NoWindow => write!(f, "no `window` found"),
+            Repr::NoDocument => write!(f, "the `window` has no document"),
+            Repr::PlatformMismatch { expected } => {
+                write!(f, "platform mismatch, expected: {}", expected)
+            }
+            Repr::RetainFailed(ptr) => write!(f, "failed to retain window handle at {:#x}", ptr),
+            Repr::WaylandNotEnabled => write!(f, "Wayland is not enabled"),
+            Repr::WaylandNotRust => write!(
+                f,
+                "the resulting Wayland handle was not created by Rust's `wayland-backend`"
+            ),
+            Repr::NotMainThread => write!(f, "window can only be used on the main thread"),
+            Repr::InvalidSelector => write!(f, "invalid canvas selector"),
+            Repr::VariantChanged => write!(f, "the window handle's platform was changed"),
+            Repr::InvalidPointer => write!(f, "invalid window pointer"),
+            Repr::WaylandWrongInterface => write!(f, "the Wayland proxy is not a `wl_surface`"),
+            Repr::WaylandDestroyed => write!(f, "the Wayland surface was destroyed"),
+            Repr::InvalidHandle => write!(f, "the window handle does not refer to a valid window"),
+            Repr::UnsupportedKind(kind) => {
+                write!(
+                    f,
+                    "{:?} window handles are not supported by this build",
+                    kind
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.0 {
+            Repr::Raw(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    #[inline]
+    fn from(err: Error) -> Self {
+        let kind = match err.kind() {
+            ErrorKind::Unsupported => std::io::ErrorKind::Unsupported,
+            ErrorKind::Unavailable => std::io::ErrorKind::NotFound,
+            _ => std::io::ErrorKind::Other,
+        };
+
+        std::io::Error::new(kind, err)
+    }
+}
+
+/// Get the value that identifies the window behind a raw handle.
+///
+/// Returns `None` if the window can't be identified by a single value.
+fn window_id(raw: &RawWindowHandle) -> Option<usize> {
+    let id = match raw {
+        RawWindowHandle::Xlib(xlib) => xlib.window as usize,
+        RawWindowHandle::Xcb(xcb) => xcb.window.get() as usize,
+        RawWindowHandle::Win32(win32) => win32.hwnd.get() as usize,
+        RawWindowHandle::WinRt(winrt) => winrt.core_window.as_ptr() as usize,
+        RawWindowHandle::Wayland(wayland) => wayland.surface.as_ptr() as usize,
+        RawWindowHandle::Drm(drm) => drm.plane as usize,
+        RawWindowHandle::Gbm(gbm) => gbm.gbm_surface.as_ptr() as usize,
+        RawWindowHandle::AppKit(appkit) => appkit.ns_view.as_ptr() as usize,
+        RawWindowHandle::UiKit(uikit) => uikit.ui_view.as_ptr() as usize,
+        RawWindowHandle::AndroidNdk(android) => android.a_native_window.as_ptr() as usize,
+        RawWindowHandle::OhosNdk(ohos) => ohos.native_window.as_ptr() as usize,
+        RawWindowHandle::Haiku(haiku) => haiku.b_window.as_ptr() as usize,
+        RawWindowHandle::Orbital(orbital) => orbital.window.as_ptr() as usize,
+        RawWindowHandle::Web(web) => web.id as usize,
+        _ => return None,
+    };
+
+    Some(id)
+}
+
+/// Tell whether two raw handles refer to the same window.
+fn same_window(a: &RawWindowHandle, b: &RawWindowHandle) -> bool {
+    // Every owned canvas gets its own slot in the `wasm-bindgen` heap, so the objects
+    // themselves need to be compared. This also compares `Web` handles against the
+    // `WebCanvas` handles they are owned as.
+    #[cfg(all(target_family = "wasm", feature = "web"))]
+    {
+        if let Some(same) = web::same_object(a, b) {
+            return same;
+        }
+    }
+
+    if mem::discriminant(a) != mem::discriminant(b) {
+        return false;
+    }
+
+    match (window_id(a), window_id(b)) {
+        (Some(id_a), Some(id_b)) => id_a == id_b,
+        _ => a == b,
+    }
+}
+
+/// Hash a raw handle consistently with [`same_window`].
+fn hash_window<H: Hasher>(raw: &RawWindowHandle, state: &mut H) {
+    mem::discriminant(raw).hash(state);
+
+    // JavaScript objects can't be hashed by identity, so only hash the variant.
+    #[cfg(all(target_family = "wasm", feature = "web"))]
+    {
+        if let RawWindowHandle::WebCanvas(_) | RawWindowHandle::WebOffscreenCanvas(_) = raw {
+            return;
+        }
+    }
+
+    match window_id(raw) {
+        Some(id) => id.hash(state),
+        None => raw.hash(state),
+    }
+}
+
+/// Check that a window handle refers to a valid window.
+fn validate(raw: &RawWindowHandle) -> Result<(), Error> {
+    let valid = match raw {
+        #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+        RawWindowHandle::AppKit(appkit) => {
+            // Views must only be messaged from the main thread.
+            if !apple::is_main_thread() {
+                return Err(Error(Repr::NotMainThread));
+            }
+
+            // SAFETY: `ns_view` points to an Objective-C object.
+            unsafe { apple::is_kind_of(appkit.ns_view, b"NSView\0") }
+        }
+
+        #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+        RawWindowHandle::UiKit(uikit) => {
+            // Views must only be messaged from the main thread.
+            if !apple::is_main_thread() {
+                return Err(Error(Repr::NotMainThread));
+            }
+
+            // SAFETY: `ui_view` points to an Objective-C object.
+            unsafe { apple::is_kind_of(uikit.ui_view, b"UIView\0") }
+        }
+
+        #[cfg(windows)]
+        RawWindowHandle::Win32(win32) => win32::is_window(win32.hwnd.get()),
+
+        // Wayland surfaces are checked when they are cloned.
+        _ => true,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(Error(Repr::InvalidHandle))
+    }
+}
+
+/// Increment reference count of the underlying handle.
+fn inc_refcount(window: WindowHandle<'_>) -> Result<Impl, Error> {
+    let result = acquire(window);
+
+    trace!(
+        platform = ?RawKind::of(&window.as_raw()),
+        window = ?window_id(&window.as_raw()),
+        ok = result.is_ok(),
+        "acquired window reference"
+    );
+
+    result
+}
+
+/// Acquire a reference to the underlying handle.
+fn acquire(window: WindowHandle<'_>) -> Result<Impl, Error> {
+    let raw = match window.as_raw() {
+        RawWindowHandle::Xlib(xlib) => {
+            // Xlib windows are just numeric ID's and are safe to use after destruction.
+            RawWindowHandle::Xlib(xlib)
+        }
+
+        RawWindowHandle::Xcb(xcb) => {
+            // XCB windows are just numeric ID's and are safe to use after destruction.
+            RawWindowHandle::Xcb(xcb)
+        }
+
+        RawWindowHandle::Win32(win32) => {
+            // Win32 windows are ID's into a thread local table.
+            // https://github.com/rust-windowing/raw-window-handle/issues/171#issuecomment-2282313064
+            RawWindowHandle::Win32(win32)
+        }
+
+        RawWindowHandle::Wayland(wayland) => {
+            // Wayland windows need to be tracked by wayland-backend.
+            // SAFETY: The window handle guarantees that the surface is valid.
+            return Ok(Impl::Wayland(unsafe { wayland::clone_handle(wayland) }?));
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )))]
+        RawWindowHandle::Drm(_) | RawWindowHandle::Gbm(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "linux" }))
+        }
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        RawWindowHandle::Drm(drm) => {
+            // Zero is never a valid DRM object ID, so this is most likely an uninitialized
+            // plane.
+            if drm.plane == 0 {
+                return Err(Error(Repr::InvalidHandle));
+            }
+
+            // DRM planes are just numeric ID's and are safe to use after destruction.
+            RawWindowHandle::Drm(drm)
+        }
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        RawWindowHandle::Gbm(gbm) => {
+            // GBM surfaces are allocated by the caller and have no reference count. We
+            // don't own them; the pointer is copied and the caller has to keep the
+            // surface alive for as long as this handle is in use.
+            RawWindowHandle::Gbm(gbm)
+        }
+
+        #[cfg(not(target_os = "android"))]
+        RawWindowHandle::AndroidNdk(_) => {
+            return Err(Error(Repr::PlatformMismatch {
+                expected: "android",
+            }))
+        }
+
+        #[cfg(all(target_os = "android", not(feature = "android")))]
+        RawWindowHandle::AndroidNdk(_) => {
+            return Err(Error(Repr::UnsupportedKind(RawKind::AndroidNdk)))
+        }
+
+        #[cfg(all(target_os = "android", feature = "android"))]
+        RawWindowHandle::AndroidNdk(android) => {
+            // The pointer can't be null, but make sure it is at least plausible.
+            if !android_pointer_is_aligned(android) {
+                return Err(Error(Repr::InvalidPointer));
+            }
+
+            // Use ANativeWindow_acquire to bump the reference count.
+            // SAFETY: `android` is a valid pointer to an `ANativeWindow`.
+            unsafe { ndk_sys::ANativeWindow_acquire(android_window(android)) };
+
+            RawWindowHandle::AndroidNdk(android)
+        }
+
+        #[cfg(not(target_vendor = "apple"))]
+        RawWindowHandle::AppKit(_) | RawWindowHandle::UiKit(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "apple" }))
+        }
+
+        #[cfg(all(target_vendor = "apple", not(feature = "appkit")))]
+        RawWindowHandle::AppKit(_) | RawWindowHandle::UiKit(_) => {
+            return Err(Error(Repr::UnsupportedKind(RawKind::of(&window.as_raw()))))
+        }
+
+        #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+        RawWindowHandle::AppKit(appkit) => {
+            use core::ptr::NonNull;
+
+            // Views must only be messaged from the main thread.
+            if !apple::is_main_thread() {
+                return Err(Error(Repr::NotMainThread));
+            }
+
+            // Objects that aren't views can't be used as windows. This can't catch dangling
+            // pointers, since asking the object anything is a message send.
+            // SAFETY: The handle guarantees that this points to an Objective-C object.
+            if !unsafe { apple::is_kind_of(appkit.ns_view, b"NSView\0") } {
+                return Err(Error(Repr::InvalidHandle));
+            }
+
+            // Use the "retain" message to retain the object.
+            // SAFETY: We know this is a valid `NSView`.
+            let view = unsafe { apple::retain(appkit.ns_view) };
+
+            RawWindowHandle::AppKit(raw_window_handle::AppKitWindowHandle::new(
+                NonNull::new(view)
+                    .ok_or(Error(Repr::RetainFailed(appkit.ns_view.as_ptr() as usize)))?,
+            ))
+        }
+
+        #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+        RawWindowHandle::UiKit(uikit) => {
+            use core::ptr::NonNull;
+
+            // Views must only be messaged from the main thread.
+            if !apple::is_main_thread() {
+                return Err(Error(Repr::NotMainThread));
+            }
+
+            // Objects that aren't views can't be used as windows. This can't catch dangling
+            // pointers, since asking the object anything is a message send.
+            // SAFETY: The handle guarantees that this points to an Objective-C object.
+            if !unsafe { apple::is_kind_of(uikit.ui_view, b"UIView\0") } {
+                return Err(Error(Repr::InvalidHandle));
+            }
+
+            // Use the "retain" message to retain the object.
+            // SAFETY: We know this is a valid `UiView`.
+            let view = unsafe { apple::retain(uikit.ui_view) };
+
+            RawWindowHandle::UiKit(raw_window_handle::UiKitWindowHandle::new(
+                NonNull::new(view)
+                    .ok_or(Error(Repr::RetainFailed(uikit.ui_view.as_ptr() as usize)))?,
+            ))
+        }
+
+        #[cfg(not(target_family = "wasm"))]
+        RawWindowHandle::Web(_)
+        | RawWindowHandle::WebCanvas(_)
+        | RawWindowHandle::WebOffscreenCanvas(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "wasm" }))
+        }
+
+        #[cfg(all(target_family = "wasm", not(feature = "web")))]
+        RawWindowHandle::Web(_)
+        | RawWindowHandle::WebCanvas(_)
+        | RawWindowHandle::WebOffscreenCanvas(_) => {
+            return Err(Error(Repr::UnsupportedKind(RawKind::of(&window.as_raw()))))
+        }
+
+        #[cfg(all(target_family = "wasm", feature = "web"))]
+        RawWindowHandle::Web(web) => {
+            use wasm_bindgen::convert::IntoWasmAbi;
+
+            // Grab the element from its data segment. This is our own reference to the
+            // element.
+            let canvas = web::find_canvas(web.id)?;
+
+            // `into_abi` moves our reference into its `wasm-bindgen` heap slot without
+            // cloning it, and `release` takes it back out using `from_abi`. This is stored as a
+            // `WebCanvas`, so `release` never sees the `Web` variant.
+            RawWindowHandle::WebCanvas(raw_window_handle::WebCanvasWindowHandle::new(
+                canvas.into_abi() as usize,
+            ))
+        }
+
+        #[cfg(all(target_family = "wasm", feature = "web"))]
+        RawWindowHandle::WebCanvas(web) => {
+            use wasm_bindgen::convert::{IntoWasmAbi, RefFromWasmAbi};
+
+            // Get the underlying canvas.
+            // SAFETY: Guaranteed to be a valid `HtmlCanvasElement`.
+            let canvas = unsafe { web_sys::HtmlCanvasElement::ref_from_abi(web.obj as _) };
+
+            // Clone the underlying JS object so we own it.
+            let canvas = (&*canvas).clone();
+
+            // Move the clone into its own heap slot, which `release` frees again.
+            RawWindowHandle::WebCanvas(raw_window_handle::WebCanvasWindowHandle::new(
+                canvas.into_abi() as usize,
+            ))
+        }
+
+        #[cfg(all(target_family = "wasm", feature = "web"))]
+        RawWindowHandle::WebOffscreenCanvas(web) => {
+            use wasm_bindgen::convert::{IntoWasmAbi, RefFromWasmAbi};
+
+            // Offscreen canvases are usually moved into a worker after using
+            // `transferControlToOffscreen`, where there is no `window` or `document`. So this
+            // has to work using nothing but the canvas object itself.
+
+            // Get the underlying canvas.
+            // SAFETY: Guaranteed to be a valid `OffscreenCanvas`.
+            let canvas = unsafe { web_sys::OffscreenCanvas::ref_from_abi(web.obj as _) };
 
             // Clone the underlying JS object so we own it.
             let canvas = (&*canvas).clone();
 
-            // The refcount is already bumped by query_selector, convert it down.
-            RawWindowHandle::WebOffscreenCanvas(
-                raw_window_handle::WebOffscreenCanvasWindowHandle::new(canvas.into_abi() as usize),
+            // The refcount is already bumped by the clone, convert it down.
+            RawWindowHandle::WebOffscreenCanvas(
+                raw_window_handle::WebOffscreenCanvasWindowHandle::new(canvas.into_abi() as usize),
+            )
+        }
+
+        #[cfg(not(target_os = "haiku"))]
+        RawWindowHandle::Haiku(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "haiku" }))
+        }
+
+        #[cfg(target_os = "haiku")]
+        RawWindowHandle::Haiku(haiku) => {
+            // `BWindow`s are `BLooper`s, which have no reference count we can bump. The
+            // pointers are copied, and it's up to the caller to keep the window alive.
+            RawWindowHandle::Haiku(haiku)
+        }
+
+        #[cfg(not(target_os = "redox"))]
+        RawWindowHandle::Orbital(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "redox" }))
+        }
+
+        #[cfg(target_os = "redox")]
+        RawWindowHandle::Orbital(orbital) => {
+            // Orbital windows are stable handles into the compositor, much like Xlib
+            // windows. They are just copied.
+            RawWindowHandle::Orbital(orbital)
+        }
+
+        #[cfg(not(target_env = "ohos"))]
+        RawWindowHandle::OhosNdk(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "ohos" }))
+        }
+
+        #[cfg(target_env = "ohos")]
+        RawWindowHandle::OhosNdk(ohos) => {
+            // Use OH_NativeWindow_NativeObjectReference to bump the reference count.
+            // SAFETY: `ohos` is a valid pointer to an `OHNativeWindow`.
+            let result =
+                unsafe { ohos::OH_NativeWindow_NativeObjectReference(ohos.native_window.as_ptr()) };
+            if result != 0 {
+                return Err(Error(Repr::RetainFailed(
+                    ohos.native_window.as_ptr() as usize
+                )));
+            }
+
+            RawWindowHandle::OhosNdk(ohos)
+        }
+
+        #[cfg(not(windows))]
+        RawWindowHandle::WinRt(_) => {
+            return Err(Error(Repr::PlatformMismatch {
+                expected: "windows",
+            }))
+        }
+
+        #[cfg(windows)]
+        RawWindowHandle::WinRt(winrt) => {
+            // WinRT objects are COM objects, so use `IUnknown::AddRef`.
+            // SAFETY: `core_window` is a valid pointer to a COM object.
+            unsafe { com::add_ref(winrt.core_window.as_ptr()) };
+
+            RawWindowHandle::WinRt(winrt)
+        }
+
+        // Default case: platform this version of the code doesn't anticipate.
+        _ => return Err(Error(Repr::UnsupportedKind(RawKind::of(&window.as_raw())))),
+    };
+
+    // SAFETY: See above comments, this is always a valid handle.
+    Ok(Impl::Direct(unsafe { WindowHandle::borrow_raw(raw) }))
+}
+
+/// Decrement reference count of the underlying handle.
+///
+/// # Safety
+///
+/// `window` must have been created via [`inc_refcount`].
+unsafe fn dec_refcount(window: WindowHandle<'static>) -> Result<(), Error> {
+    // SAFETY: The caller upholds the same guarantees.
+    let result = unsafe { release(window) };
+
+    // A reference that failed to be released is still outstanding.
+    #[cfg(feature = "leak-check")]
+    if result.is_ok() {
+        leak_check::released();
+    }
+
+    trace!(
+        platform = ?RawKind::of(&window.as_raw()),
+        window = ?window_id(&window.as_raw()),
+        ok = result.is_ok(),
+        "released window reference"
+    );
+
+    result
+}
+
+/// Release the reference to the underlying handle.
+///
+/// # Safety
+///
+/// `window` must have been created via [`inc_refcount`].
+unsafe fn release(window: WindowHandle<'static>) -> Result<(), Error> {
+    match window.as_raw() {
+        RawWindowHandle::Xlib(_) => {
+            // We did nothing with the window above, so no need to do anything
+            // here either.
+        }
+
+        RawWindowHandle::Xcb(_) => {
+            // We did nothing with the window above, so no need to do anything
+            // here either.
+        }
+
+        RawWindowHandle::Win32(_) => {
+            // We did nothing with the window above, so no need to do anything
+            // here either.
+        }
+
+        RawWindowHandle::Wayland(_) => {
+            // inc_refcount never creates this variant, since Wayland objects are tracked by
+            // wayland-backend instead. There is no reference to release, so don't panic.
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )))]
+        RawWindowHandle::Drm(_) | RawWindowHandle::Gbm(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "linux" }))
+        }
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        RawWindowHandle::Drm(_) => {
+            // We did nothing with the window above, so no need to do anything
+            // here either.
+        }
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        RawWindowHandle::Gbm(_) => {
+            // We did nothing with the window above, so no need to do anything
+            // here either.
+        }
+
+        #[cfg(not(target_os = "android"))]
+        RawWindowHandle::AndroidNdk(_) => {
+            return Err(Error(Repr::PlatformMismatch {
+                expected: "android",
+            }))
+        }
+
+        #[cfg(all(target_os = "android", not(feature = "android")))]
+        RawWindowHandle::AndroidNdk(_) => {
+            return Err(Error(Repr::UnsupportedKind(RawKind::AndroidNdk)))
+        }
+
+        #[cfg(all(target_os = "android", feature = "android"))]
+        RawWindowHandle::AndroidNdk(android) => {
+            if !android_pointer_is_aligned(android) {
+                return Err(Error(Repr::InvalidPointer));
+            }
+
+            // Use ANativeWindow_release to release the reference count.
+            // SAFETY: `android` is a valid pointer to an `ANativeWindow`.
+            unsafe { ndk_sys::ANativeWindow_release(android_window(android)) };
+        }
+
+        #[cfg(not(target_vendor = "apple"))]
+        RawWindowHandle::AppKit(_) | RawWindowHandle::UiKit(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "apple" }))
+        }
+
+        #[cfg(all(target_vendor = "apple", not(feature = "appkit")))]
+        RawWindowHandle::AppKit(_) | RawWindowHandle::UiKit(_) => {
+            return Err(Error(Repr::UnsupportedKind(RawKind::of(&window.as_raw()))))
+        }
+
+        #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+        RawWindowHandle::AppKit(appkit) => {
+            // Views must only be messaged from the main thread.
+            if !apple::is_main_thread() {
+                return Err(Error(Repr::NotMainThread));
+            }
+
+            // Use the "release" message to release the object.
+            // SAFETY: We know this is a valid `NsView`.
+            unsafe { apple::release(appkit.ns_view) };
+        }
+
+        #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+        RawWindowHandle::UiKit(uikit) => {
+            // Views must only be messaged from the main thread.
+            if !apple::is_main_thread() {
+                return Err(Error(Repr::NotMainThread));
+            }
+
+            // Use the "release" message to release the object.
+            // SAFETY: We know this is a valid `UiView`.
+            unsafe { apple::release(uikit.ui_view) };
+        }
+
+        RawWindowHandle::Web(_) => unreachable!("inc_refcount never constructs this variant"),
+
+        #[cfg(not(target_family = "wasm"))]
+        RawWindowHandle::WebCanvas(_) | RawWindowHandle::WebOffscreenCanvas(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "wasm" }))
+        }
+
+        #[cfg(all(target_family = "wasm", not(feature = "web")))]
+        RawWindowHandle::WebCanvas(_) | RawWindowHandle::WebOffscreenCanvas(_) => {
+            return Err(Error(Repr::UnsupportedKind(RawKind::of(&window.as_raw()))))
+        }
+
+        #[cfg(all(target_family = "wasm", feature = "web"))]
+        RawWindowHandle::WebCanvas(web) => {
+            use wasm_bindgen::convert::FromWasmAbi;
+
+            // `acquire` moved a reference into this heap slot, for both `Web` and `WebCanvas`
+            // handles. Take it back out and drop it, which frees the slot.
+            // SAFETY: This is a valid, owned object as constructed above.
+            let canvas = unsafe { web_sys::HtmlCanvasElement::from_abi(web.obj as _) };
+            drop(canvas);
+        }
+
+        #[cfg(all(target_family = "wasm", feature = "web"))]
+        RawWindowHandle::WebOffscreenCanvas(web) => {
+            use wasm_bindgen::convert::FromWasmAbi;
+
+            // We created a new object here. Drop it.
+            // SAFETY: This is a valid, owned object as constructed above.
+            let canvas = unsafe { web_sys::OffscreenCanvas::from_abi(web.obj as _) };
+            drop(canvas);
+        }
+
+        #[cfg(not(target_os = "haiku"))]
+        RawWindowHandle::Haiku(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "haiku" }))
+        }
+
+        #[cfg(target_os = "haiku")]
+        RawWindowHandle::Haiku(_) => {
+            // We did nothing with the window above, so no need to do anything
+            // here either.
+        }
+
+        #[cfg(not(target_os = "redox"))]
+        RawWindowHandle::Orbital(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "redox" }))
+        }
+
+        #[cfg(target_os = "redox")]
+        RawWindowHandle::Orbital(_) => {
+            // We did nothing with the window above, so no need to do anything
+            // here either.
+        }
+
+        #[cfg(not(target_env = "ohos"))]
+        RawWindowHandle::OhosNdk(_) => {
+            return Err(Error(Repr::PlatformMismatch { expected: "ohos" }))
+        }
+
+        #[cfg(target_env = "ohos")]
+        RawWindowHandle::OhosNdk(ohos) => {
+            // Use OH_NativeWindow_NativeObjectUnreference to drop the reference count.
+            // SAFETY: `ohos` is a valid pointer to an `OHNativeWindow`.
+            unsafe { ohos::OH_NativeWindow_NativeObjectUnreference(ohos.native_window.as_ptr()) };
+        }
+
+        #[cfg(not(windows))]
+        RawWindowHandle::WinRt(_) => {
+            return Err(Error(Repr::PlatformMismatch {
+                expected: "windows",
+            }))
+        }
+
+        #[cfg(windows)]
+        RawWindowHandle::WinRt(winrt) => {
+            // Use `IUnknown::Release` to release the object.
+            // SAFETY: `core_window` is a valid pointer to a COM object that we hold a
+            // reference to.
+            unsafe { com::release(winrt.core_window.as_ptr()) };
+        }
+
+        // Default case: platform this version of the code doesn't anticipate.
+        _ => return Err(Error(Repr::UnsupportedKind(RawKind::of(&window.as_raw())))),
+    }
+
+    Ok(())
+}
+
+/// Tell whether an Android window pointer is properly aligned.
+///
+/// `ANativeWindow` is opaque, but it starts with pointer sized fields.
+#[cfg(all(target_os = "android", feature = "android"))]
+fn android_pointer_is_aligned(android: raw_window_handle::AndroidNdkWindowHandle) -> bool {
+    android.a_native_window.as_ptr() as usize % mem::align_of::<usize>() == 0
+}
+
+/// Get the `ndk-sys` pointer to an Android window.
+///
+/// The type is spelled out so that a change to the `ANativeWindow` binding in `ndk-sys`
+/// becomes a build error here, rather than a silent cast. The bindings themselves are pinned
+/// by the `ndk-sys` 0.6 requirement in `Cargo.toml`.
+#[cfg(all(target_os = "android", feature = "android"))]
+fn android_window(
+    android: raw_window_handle::AndroidNdkWindowHandle,
+) -> *mut ndk_sys::ANativeWindow {
+    android
+        .a_native_window
+        .as_ptr()
+        .cast::<ndk_sys::ANativeWindow>()
+}
+
+/// Possible error codes.
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Repr {
+    /// Underlying [`raw-window-handle`] error.
+    Raw(HandleError),
+
+    /// This is the wrong platform to use this function.
+    PlatformMismatch {
+        /// The platform we expected.
+        expected: &'static str,
+    },
+
+    /// There is no `window` on web.
+    NoWindow,
+
+    /// The `window` has no document.
+    NoDocument,
+
+    /// Canvas not found with the specific ID.
+    CanvasNotFound(u32),
+
+    /// Retaining the window at this address failed.
+    RetainFailed(usize),
+
+    /// Wayland is not enabled.
+    WaylandNotEnabled,
+
+    /// The resulting Wayland handle was not created by Rust's `wayland-backend`.
+    WaylandNotRust,
+
+    /// Not running on the main thread.
+    NotMainThread,
+
+    /// `querySelector` rejected the canvas selector.
+    InvalidSelector,
+
+    /// The platform of a mapped handle was changed.
+    VariantChanged,
+
+    /// The window pointer is misaligned.
+    InvalidPointer,
+
+    /// The Wayland proxy has the wrong interface.
+    WaylandWrongInterface,
+
+    /// The Wayland proxy was destroyed.
+    WaylandDestroyed,
+
+    /// The window handle does not refer to a valid window.
+    InvalidHandle,
+
+    /// This kind of window handle is not supported by this build.
+    UnsupportedKind(RawKind),
+}
+
+#[cfg(any(
+    not(feature = "wayland"),
+    not(all(
+        unix,
+        not(any(
+            target_os = "redox",
+            target_family = "wasm",
+            target_os = "android",
+            target_vendor = "apple"
+        ))
+    ))
+))]
+mod wayland {
+    /// Wayland handle.
+    ///
+    /// This can never be created. The field is private so that code outside of this module
+    /// still type checks as if it could.
+    #[derive(Clone, Debug)]
+    pub(super) struct WaylandHandle(core::convert::Infallible);
+
+    /// Create a new `WaylandHandle` from the raw wayland handle.
+    pub(super) unsafe fn clone_handle(
+        _handle: raw_window_handle::WaylandWindowHandle,
+    ) -> Result<WaylandHandle, crate::Error> {
+        Err(crate::Error(crate::Repr::WaylandNotEnabled))
+    }
+
+    /// Convert the `WaylandHandle` into a window handle.
+    pub(super) fn as_ptr(
+        handle: &WaylandHandle,
+    ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+        match handle.0 {}
+    }
+
+    /// Get the raw handle for the `WaylandHandle`.
+    pub(super) fn as_raw(handle: &WaylandHandle) -> raw_window_handle::WaylandWindowHandle {
+        match handle.0 {}
+    }
+
+    /// Tell whether the surface is still alive.
+    pub(super) fn is_alive(handle: &WaylandHandle) -> bool {
+        match handle.0 {}
+    }
+
+    /// A weak reference to a `WaylandHandle`.
+    ///
+    /// This can never be created, see `WaylandHandle`.
+    pub(super) struct WeakWaylandHandle(core::convert::Infallible);
+
+    /// Create a weak reference to a `WaylandHandle`.
+    pub(super) fn downgrade(handle: &WaylandHandle) -> WeakWaylandHandle {
+        match handle.0 {}
+    }
+
+    /// Get a `WaylandHandle` back, if one of its clones and the surface are still alive.
+    pub(super) fn upgrade(weak: &WeakWaylandHandle) -> Option<WaylandHandle> {
+        match weak.0 {}
+    }
+
+    /// Tell whether two `WaylandHandle`s refer to the same surface.
+    pub(super) fn same_surface(a: &WaylandHandle, _b: &WaylandHandle) -> bool {
+        match a.0 {}
+    }
+
+    /// Tell whether two `WaylandHandle`s belong to the same display.
+    pub(super) fn same_display(a: &WaylandHandle, _b: &WaylandHandle) -> bool {
+        match a.0 {}
+    }
+
+    /// Hash the surface behind a `WaylandHandle`.
+    pub(super) fn hash_surface<H: core::hash::Hasher>(handle: &WaylandHandle, _state: &mut H) {
+        match handle.0 {}
+    }
+
+    /// Wayland display.
+    ///
+    /// This can never be created, see `WaylandHandle`.
+    #[derive(Clone, Debug)]
+    pub(super) struct WaylandDisplay(core::convert::Infallible);
+
+    /// Create a new `WaylandDisplay` from the raw wayland handle.
+    pub(super) unsafe fn clone_display(
+        _handle: raw_window_handle::WaylandDisplayHandle,
+    ) -> Result<WaylandDisplay, crate::Error> {
+        Err(crate::Error(crate::Repr::WaylandNotEnabled))
+    }
+
+    /// Convert the `WaylandDisplay` into a display handle.
+    pub(super) fn display_as_ptr(
+        handle: &WaylandDisplay,
+    ) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+        match handle.0 {}
+    }
+}
+
+#[cfg(all(
+    feature = "wayland",
+    unix,
+    not(any(
+        target_os = "redox",
+        target_family = "wasm",
+        target_os = "android",
+        target_vendor = "apple"
+    ))
+))]
+mod wayland {
+    use wayland_backend::sys::client as wc;
+    use wayland_client::Proxy;
+
+    use std::sync::{Arc, Weak};
+
+    /// Tracked Wayland handle.
+    ///
+    /// Clones share the same surface, so that weak references can tell when the last clone
+    /// was dropped.
+    #[derive(Clone)]
+    pub(super) struct WaylandHandle(Arc<Surface>);
+
+    /// A weak reference to a `WaylandHandle`.
+    ///
+    /// This doesn't keep the backend alive.
+    pub(super) struct WeakWaylandHandle(Weak<Surface>);
+
+    /// The surface shared between clones of a `WaylandHandle`.
+    struct Surface {
+        /// The Wayland object ID.
+        id: wc::ObjectId,
+
+        /// The original `wl_surface` pointer.
+        surface: core::ptr::NonNull<core::ffi::c_void>,
+
+        /// The backend the surface belongs to.
+        backend: wc::Backend,
+    }
+
+    impl core::fmt::Debug for WaylandHandle {
+        #[inline]
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            core::fmt::Debug::fmt(&self.0.id, f)
+        }
+    }
+
+    /// Get a `WaylandHandle` from a `*mut wl_proxy`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must point to a valid `wl_proxy`.
+    pub(super) unsafe fn clone_handle(
+        handle: raw_window_handle::WaylandWindowHandle,
+    ) -> Result<WaylandHandle, crate::Error> {
+        let ptr = handle.surface;
+
+        // Create the `ObjectId` from the `wl_surface` pointer. This checks the interface of the
+        // proxy using `wl_proxy_get_class`, and fails if it isn't a `wl_surface`.
+        // SAFETY: The caller guarantees that this is a valid `wl_proxy`.
+        let id = unsafe {
+            wc::ObjectId::from_ptr(
+                wayland_client::protocol::wl_surface::WlSurface::interface(),
+                ptr.as_ptr().cast(),
+            )
+        }
+        .map_err(|_| crate::Error(crate::Repr::WaylandWrongInterface))?;
+
+        // SAFETY: The caller guarantees that the proxy is valid.
+        let backend = unsafe { backend_from_id(&id) }?;
+
+        trace!(surface = ?id, "tracking Wayland surface");
+
+        Ok(WaylandHandle(Arc::new(Surface {
+            id,
+            surface: ptr,
+            backend,
+        })))
+    }
+
+    /// Get the `Backend` that a Rust-managed surface belongs to.
+    ///
+    /// The backend is only returned once it was able to look up the surface. If the surface
+    /// or its connection were torn down, this returns `WaylandDestroyed`.
+    ///
+    /// # Safety
+    ///
+    /// The `wl_proxy` behind `id` must stay alive for the duration of this call, since its
+    /// display is shared with the returned backend.
+    unsafe fn backend_from_id(id: &wc::ObjectId) -> Result<wc::Backend, crate::Error> {
+        // A proxy that was already destroyed no longer has a display.
+        let display_ptr = id.display_ptr();
+        if display_ptr.is_null() {
+            return Err(crate::Error(crate::Repr::WaylandDestroyed));
+        }
+
+        // SAFETY: The caller guarantees that the proxy is alive, and the display outlives
+        // all of its proxies.
+        let backend = unsafe { wc::Backend::from_foreign_display(display_ptr) };
+
+        // Make sure the backend can actually see the surface before using it for anything
+        // else. Surfaces owned by Rust's wayland-backend know when they are destroyed.
+        if backend.info(id.clone()).is_err() {
+            return Err(crate::Error(crate::Repr::WaylandDestroyed));
+        }
+
+        /* Ensure the object is owned by Rust's wayland-backend. */
+        if backend.get_data(id.clone()).is_err() {
+            return Err(crate::Error(crate::Repr::WaylandNotRust));
+        }
+
+        Ok(backend)
+    }
+
+    /// Convert the `WaylandHandle` into a window handle.
+    pub(super) fn as_ptr(
+        handle: &WaylandHandle,
+    ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+        match core::ptr::NonNull::new(handle.0.id.as_ptr()) {
+            None => Err(raw_window_handle::HandleError::Unavailable),
+            Some(non_null) => {
+                let raw = raw_window_handle::WaylandWindowHandle::new(non_null.cast()).into();
+
+                // SAFETY: The proxy is being kept alive, so we know it's valid.
+                Ok(unsafe { raw_window_handle::WindowHandle::borrow_raw(raw) })
+            }
+        }
+    }
+
+    /// Get the raw handle for the `WaylandHandle`.
+    pub(super) fn as_raw(handle: &WaylandHandle) -> raw_window_handle::WaylandWindowHandle {
+        raw_window_handle::WaylandWindowHandle::new(handle.0.surface)
+    }
+
+    /// Get the object ID of the surface.
+    pub(super) fn object_id(handle: &WaylandHandle) -> &wc::ObjectId {
+        &handle.0.id
+    }
+
+    /// Get the backend the surface belongs to.
+    pub(super) fn backend(handle: &WaylandHandle) -> &wc::Backend {
+        &handle.0.backend
+    }
+
+    /// Get the `wl_display` the surface belongs to.
+    pub(super) fn display_ptr(
+        handle: &WaylandHandle,
+    ) -> Option<core::ptr::NonNull<core::ffi::c_void>> {
+        core::ptr::NonNull::new(handle.0.backend.display_ptr().cast())
+    }
+
+    /// Tell whether the surface is still alive.
+    pub(super) fn is_alive(handle: &WaylandHandle) -> bool {
+        handle.0.backend.info(handle.0.id.clone()).is_ok()
+    }
+
+    /// Create a weak reference to a `WaylandHandle`.
+    pub(super) fn downgrade(handle: &WaylandHandle) -> WeakWaylandHandle {
+        WeakWaylandHandle(Arc::downgrade(&handle.0))
+    }
+
+    /// Get a `WaylandHandle` back, if one of its clones and the surface are still alive.
+    pub(super) fn upgrade(weak: &WeakWaylandHandle) -> Option<WaylandHandle> {
+        let handle = WaylandHandle(weak.0.upgrade()?);
+        if is_alive(&handle) {
+            Some(handle)
+        } else {
+            None
+        }
+    }
+
+    /// Tell whether two `WaylandHandle`s refer to the same surface.
+    pub(super) fn same_surface(a: &WaylandHandle, b: &WaylandHandle) -> bool {
+        a.0.id == b.0.id
+    }
+
+    /// Tell whether two `WaylandHandle`s belong to the same display.
+    pub(super) fn same_display(a: &WaylandHandle, b: &WaylandHandle) -> bool {
+        a.0.backend.display_ptr() == b.0.backend.display_ptr()
+    }
+
+    /// Hash the surface behind a `WaylandHandle`.
+    pub(super) fn hash_surface<H: core::hash::Hasher>(handle: &WaylandHandle, state: &mut H) {
+        core::hash::Hash::hash(&handle.0.id, state);
+    }
+
+    /// Tracked Wayland display.
+    #[derive(Clone)]
+    pub(super) struct WaylandDisplay {
+        /// The backend wrapping the `wl_display`.
+        backend: wc::Backend,
+    }
+
+    /// Get a `WaylandDisplay` from a `*mut wl_display`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must point to a valid `wl_display`.
+    pub(super) unsafe fn clone_display(
+        handle: raw_window_handle::WaylandDisplayHandle,
+    ) -> Result<WaylandDisplay, crate::Error> {
+        // SAFETY: The caller guarantees that this is a valid `wl_display`.
+        let backend = unsafe { wc::Backend::from_foreign_display(handle.display.as_ptr().cast()) };
+        Ok(WaylandDisplay { backend })
+    }
+
+    /// Convert the `WaylandDisplay` into a display handle.
+    pub(super) fn display_as_ptr(
+        handle: &WaylandDisplay,
+    ) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+        match core::ptr::NonNull::new(handle.backend.display_ptr()) {
+            None => Err(raw_window_handle::HandleError::Unavailable),
+            Some(non_null) => {
+                let raw = raw_window_handle::WaylandDisplayHandle::new(non_null.cast()).into();
+
+                // SAFETY: The backend is being kept alive, so we know it's valid.
+                Ok(unsafe { raw_window_handle::DisplayHandle::borrow_raw(raw) })
+            }
+        }
+    }
+}
+
+/// Escape a CSS identifier, the same way `CSS.escape` does.
+///
+/// An empty identifier stays empty, which `querySelector` rejects as invalid. This lives
+/// outside of the `web` module so it can be tested on every platform.
+#[cfg(any(test, all(target_family = "wasm", feature = "web")))]
+fn escape_ident(ident: &str) -> String {
+    use core::fmt::Write;
+
+    let mut escaped = String::with_capacity(ident.len());
+    let mut chars = ident.chars().peekable();
+    let mut first = true;
+    let mut leading_dash = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\0' => escaped.push('\u{fffd}'),
+
+            // Control characters and leading digits need to be hex escaped.
+            '\x01'..='\x1f' | '\x7f' => write!(escaped, "\\{:x} ", c as u32).unwrap(),
+            '0'..='9' if first || leading_dash => write!(escaped, "\\{:x} ", c as u32).unwrap(),
+
+            // A lone dash isn't a valid identifier.
+            '-' if first && chars.peek().is_none() => escaped.push_str("\\-"),
+
+            '-' | '_' | 'a'..='z' | 'A'..='Z' | '0'..='9' => escaped.push(c),
+            c if !c.is_ascii() => escaped.push(c),
+
+            // Everything else is escaped as-is.
+            c => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+        }
+
+        leading_dash = first && c == '-';
+        first = false;
+    }
+
+    escaped
+}
+
+#[cfg(all(target_family = "wasm", feature = "web"))]
+mod web {
+    use super::{escape_ident, Error, Repr};
+
+    use raw_window_handle::RawWindowHandle;
+    use std::cell::RefCell;
+    use wasm_bindgen::convert::RefFromWasmAbi;
+    use wasm_bindgen::JsValue;
+
+    std::thread_local! {
+        /// The current document, looked up once.
+        static DOCUMENT: RefCell<Option<web_sys::Document>> = RefCell::new(None);
+    }
+
+    /// Find the canvas with the given `data-raw-handle` ID.
+    ///
+    /// This is only needed for `Web` handles. Workers have no window, so it fails with
+    /// `NoWindow` there.
+    pub(super) fn find_canvas(id: u32) -> Result<web_sys::Element, Error> {
+        find_canvas_with_attr("data-raw-handle", id)
+    }
+
+    /// Find the canvas whose `attr` attribute is set to the given ID.
+    ///
+    /// Only the document is cached. Canvases are looked up again every time, since a cached
+    /// element would be kept alive after it is removed from the document.
+    pub(super) fn find_canvas_with_attr(attr: &str, id: u32) -> Result<web_sys::Element, Error> {
+        document()?
+            .query_selector(&format!("canvas[{}=\"{}\"]", escape_ident(attr), id))
+            // `querySelector` only throws an error if the selector is invalid.
+            .map_err(|_| Error(Repr::InvalidSelector))?
+            .ok_or(Error(Repr::CanvasNotFound(id)))
+    }
+
+    /// Get the current document.
+    fn document() -> Result<web_sys::Document, Error> {
+        DOCUMENT.with(|document| {
+            let mut document = document.borrow_mut();
+            if let Some(document) = &*document {
+                return Ok(document.clone());
+            }
+
+            let new = web_sys::window()
+                .ok_or(Error(Repr::NoWindow))?
+                .document()
+                .ok_or(Error(Repr::NoDocument))?;
+            *document = Some(new.clone());
+            Ok(new)
+        })
+    }
+
+    /// Tell whether two web handles refer to the same JavaScript object.
+    ///
+    /// `Web` handles are owned as `WebCanvas` handles, so a `Web` handle is compared against
+    /// a `WebCanvas` handle by looking up its canvas. Returns `None` if the handles are not
+    /// JavaScript objects.
+    pub(super) fn same_object(a: &RawWindowHandle, b: &RawWindowHandle) -> Option<bool> {
+        let (a, b) = match (a, b) {
+            (RawWindowHandle::WebCanvas(a), RawWindowHandle::WebCanvas(b)) => (a.obj, b.obj),
+            (RawWindowHandle::WebOffscreenCanvas(a), RawWindowHandle::WebOffscreenCanvas(b)) => {
+                (a.obj, b.obj)
+            }
+            (RawWindowHandle::Web(web), RawWindowHandle::WebCanvas(canvas))
+            | (RawWindowHandle::WebCanvas(canvas), RawWindowHandle::Web(web)) => {
+                // A canvas that can't be found can't be the same as one that exists.
+                let element = match find_canvas(web.id) {
+                    Ok(element) => element,
+                    Err(_) => return Some(false),
+                };
+
+                // SAFETY: Canvas handles always hold valid JavaScript objects.
+                let canvas = unsafe { JsValue::ref_from_abi(canvas.obj as _) };
+
+                let element: &JsValue = element.as_ref();
+                return Some(*element == *canvas);
+            }
+            _ => return None,
+        };
+
+        // SAFETY: Canvas handles always hold valid JavaScript objects.
+        let a = unsafe { JsValue::ref_from_abi(a as _) };
+        let b = unsafe { JsValue::ref_from_abi(b as _) };
+
+        Some(*a == *b)
+    }
+}
+
+#[cfg(all(target_vendor = "apple", feature = "appkit"))]
+mod apple {
+    //! Helpers for the Objective-C runtime.
+    //!
+    //! With the `objc2` feature, this goes through `objc2`. Without it, the runtime is called
+    //! directly through `objc_msgSend`.
+
+    use core::cell::UnsafeCell;
+    use core::ffi::c_void;
+    use core::ptr::{self, NonNull};
+
+    #[cfg(feature = "objc2")]
+    use objc2::ffi;
+
+    #[cfg(not(feature = "objc2"))]
+    mod ffi {
+        //! Minimal bindings to the Objective-C runtime.
+
+        use core::ffi::{c_char, c_int, c_void};
+
+        /// An Objective-C object.
+        #[allow(non_camel_case_types)]
+        #[repr(C)]
+        pub(super) struct objc_object {
+            _private: [u8; 0],
+        }
+
+        #[link(name = "objc", kind = "dylib")]
+        extern "C" {
+            pub(super) fn objc_initWeak(
+                location: *mut *mut objc_object,
+                object: *mut objc_object,
+            ) -> *mut objc_object;
+            pub(super) fn objc_loadWeakRetained(
+                location: *mut *mut objc_object,
+            ) -> *mut objc_object;
+            pub(super) fn objc_destroyWeak(location: *mut *mut objc_object);
+            pub(super) fn objc_msgSend();
+            pub(super) fn sel_registerName(name: *const c_char) -> *const c_void;
+            pub(super) fn objc_getClass(name: *const c_char) -> *const c_void;
+        }
+
+        extern "C" {
+            pub(super) fn pthread_main_np() -> c_int;
+        }
+    }
+
+    /// Tell whether we are running on the main thread.
+    pub(super) fn is_main_thread() -> bool {
+        #[cfg(feature = "objc2")]
+        {
+            objc2::MainThreadMarker::new().is_some()
+        }
+
+        #[cfg(not(feature = "objc2"))]
+        {
+            // SAFETY: `pthread_main_np` can be called from any thread.
+            unsafe { ffi::pthread_main_np() != 0 }
+        }
+    }
+
+    /// Send the `retain` message to an object, returning the result.
+    ///
+    /// # Safety
+    ///
+    /// `object` must be a valid Objective-C object.
+    pub(super) unsafe fn retain(object: NonNull<c_void>) -> *mut c_void {
+        trace!(
+            view = ?object,
+            // SAFETY: The caller guarantees that `object` is valid.
+            retain_count = unsafe { retain_count(object) },
+            "retaining view"
+        );
+
+        // SAFETY: The caller guarantees that `object` is valid.
+        let retained = unsafe { send_retain(object) };
+
+        trace!(
+            view = ?object,
+            // SAFETY: The caller guarantees that `object` is valid.
+            retain_count = unsafe { retain_count(object) },
+            "retained view"
+        );
+
+        retained
+    }
+
+    /// Send the `retain` message.
+    ///
+    /// # Safety
+    ///
+    /// `object` must be a valid Objective-C object.
+    unsafe fn send_retain(object: NonNull<c_void>) -> *mut c_void {
+        #[cfg(feature = "objc2")]
+        {
+            use objc2::runtime::NSObject;
+
+            let object: *mut NSObject = object.as_ptr().cast();
+            // SAFETY: The caller guarantees that `object` is valid.
+            let object: *mut NSObject = unsafe { objc2::msg_send![object, retain] };
+            object.cast()
+        }
+
+        #[cfg(not(feature = "objc2"))]
+        {
+            // SAFETY: `msg_send` has the signature of `retain`.
+            let msg_send: unsafe extern "C" fn(*mut c_void, *const c_void) -> *mut c_void =
+                unsafe { core::mem::transmute(ffi::objc_msgSend as unsafe extern "C" fn()) };
+
+            // SAFETY: The caller guarantees that `object` is valid.
+            unsafe { msg_send(object.as_ptr(), selector(b"retain\0")) }
+        }
+    }
+
+    /// Send the `release` message to an object.
+    ///
+    /// # Safety
+    ///
+    /// `object` must be a valid Objective-C object that the caller holds a reference to.
+    pub(super) unsafe fn release(object: NonNull<c_void>) {
+        trace!(
+            view = ?object,
+            // SAFETY: The caller guarantees that `object` is valid.
+            retain_count = unsafe { retain_count(object) },
+            "releasing view"
+        );
+
+        // SAFETY: The caller guarantees that `object` is valid.
+        unsafe { send_release(object) };
+
+        // The view may have been deallocated, so don't touch it anymore.
+        trace!(view = ?object, "released view");
+    }
+
+    /// Send the `release` message.
+    ///
+    /// # Safety
+    ///
+    /// `object` must be a valid Objective-C object that the caller holds a reference to.
+    unsafe fn send_release(object: NonNull<c_void>) {
+        #[cfg(feature = "objc2")]
+        {
+            use objc2::runtime::NSObject;
+
+            let object: *mut NSObject = object.as_ptr().cast();
+            // SAFETY: The caller guarantees that `object` is valid.
+            let _: () = unsafe { objc2::msg_send![object, release] };
+        }
+
+        #[cfg(not(feature = "objc2"))]
+        {
+            // SAFETY: `msg_send` has the signature of `release`.
+            let msg_send: unsafe extern "C" fn(*mut c_void, *const c_void) =
+                unsafe { core::mem::transmute(ffi::objc_msgSend as unsafe extern "C" fn()) };
+
+            // SAFETY: The caller guarantees that `object` is valid.
+            unsafe { msg_send(object.as_ptr(), selector(b"release\0")) }
+        }
+    }
+
+    /// Send the `contentView` message to an `NSWindow`, returning the result.
+    ///
+    /// # Safety
+    ///
+    /// `window` must be a valid `NSWindow`.
+    #[cfg(target_os = "macos")]
+    pub(super) unsafe fn content_view(window: NonNull<c_void>) -> *mut c_void {
+        #[cfg(feature = "objc2")]
+        {
+            use objc2::runtime::NSObject;
+
+            let window: *mut NSObject = window.as_ptr().cast();
+            // SAFETY: The caller guarantees that `window` is valid.
+            let view: *mut NSObject = unsafe { objc2::msg_send![window, contentView] };
+            view.cast()
+        }
+
+        #[cfg(not(feature = "objc2"))]
+        {
+            // SAFETY: `msg_send` has the signature of `contentView`.
+            let msg_send: unsafe extern "C" fn(*mut c_void, *const c_void) -> *mut c_void =
+                unsafe { core::mem::transmute(ffi::objc_msgSend as unsafe extern "C" fn()) };
+
+            // SAFETY: The caller guarantees that `window` is valid.
+            unsafe { msg_send(window.as_ptr(), selector(b"contentView\0")) }
+        }
+    }
+
+    /// Send the `retainCount` message to an object.
+    ///
+    /// # Safety
+    ///
+    /// `object` must be a valid Objective-C object.
+    pub(super) unsafe fn retain_count(object: NonNull<c_void>) -> usize {
+        #[cfg(feature = "objc2")]
+        {
+            use objc2::runtime::NSObject;
+
+            let object: *mut NSObject = object.as_ptr().cast();
+            // SAFETY: The caller guarantees that `object` is valid.
+            unsafe { objc2::msg_send![object, retainCount] }
+        }
+
+        #[cfg(not(feature = "objc2"))]
+        {
+            // SAFETY: `msg_send` has the signature of `retainCount`.
+            let msg_send: unsafe extern "C" fn(*mut c_void, *const c_void) -> usize =
+                unsafe { core::mem::transmute(ffi::objc_msgSend as unsafe extern "C" fn()) };
+
+            // SAFETY: The caller guarantees that `object` is valid.
+            unsafe { msg_send(object.as_ptr(), selector(b"retainCount\0")) }
+        }
+    }
+
+    /// Tell whether an object is an instance of the class with the given nul-terminated
+    /// name, or of one of its subclasses.
+    ///
+    /// # Safety
+    ///
+    /// `object` must be a valid Objective-C object.
+    pub(super) unsafe fn is_kind_of(object: NonNull<c_void>, class: &[u8]) -> bool {
+        debug_assert_eq!(class.last(), Some(&0));
+
+        #[cfg(feature = "objc2")]
+        {
+            use objc2::runtime::{AnyClass, NSObject};
+
+            let name = match core::str::from_utf8(&class[..class.len() - 1]) {
+                Ok(name) => name,
+                Err(_) => return false,
+            };
+            let class = match AnyClass::get(name) {
+                Some(class) => class,
+                None => return false,
+            };
+
+            let object: *mut NSObject = object.as_ptr().cast();
+            // SAFETY: The caller guarantees that `object` is valid.
+            unsafe { objc2::msg_send![object, isKindOfClass: class] }
+        }
+
+        #[cfg(not(feature = "objc2"))]
+        {
+            // SAFETY: `class` is a nul-terminated string.
+            let class = unsafe { ffi::objc_getClass(class.as_ptr().cast()) };
+            if class.is_null() {
+                return false;
+            }
+
+            // SAFETY: `msg_send` has the signature of `isKindOfClass:`. `BOOL` is a single
+            // byte on every Apple platform.
+            let msg_send: unsafe extern "C" fn(*mut c_void, *const c_void, *const c_void) -> u8 =
+                unsafe { core::mem::transmute(ffi::objc_msgSend as unsafe extern "C" fn()) };
+
+            // SAFETY: The caller guarantees that `object` is valid.
+            unsafe { msg_send(object.as_ptr(), selector(b"isKindOfClass:\0"), class) != 0 }
+        }
+    }
+
+    /// Look up a selector by its nul-terminated name.
+    #[cfg(not(feature = "objc2"))]
+    fn selector(name: &[u8]) -> *const c_void {
+        debug_assert_eq!(name.last(), Some(&0));
+
+        // SAFETY: `name` is a nul-terminated string.
+        unsafe { ffi::sel_registerName(name.as_ptr().cast()) }
+    }
+
+    /// Weak reference to an Objective-C object.
+    pub(super) struct WeakRef {
+        /// The location registered with the runtime. It must never move.
+        location: Box<UnsafeCell<*mut ffi::objc_object>>,
+    }
+
+    impl WeakRef {
+        /// Create a weak reference to `object`.
+        ///
+        /// # Safety
+        ///
+        /// `object` must be a valid Objective-C object.
+        pub(super) unsafe fn new(object: NonNull<c_void>) -> Self {
+            let location = Box::new(UnsafeCell::new(ptr::null_mut()));
+
+            // SAFETY: `location` is boxed so it never moves, and the caller guarantees that
+            // `object` is valid.
+            unsafe { ffi::objc_initWeak(location.get(), object.as_ptr().cast()) };
+
+            Self { location }
+        }
+
+        /// Get a retained pointer to the object, if it is still alive.
+        pub(super) fn load(&self) -> Option<NonNull<c_void>> {
+            // SAFETY: `location` was registered using `objc_initWeak`.
+            let object = unsafe { ffi::objc_loadWeakRetained(self.location.get()) };
+            NonNull::new(object.cast())
+        }
+    }
+
+    impl Drop for WeakRef {
+        fn drop(&mut self) {
+            // SAFETY: `location` was registered using `objc_initWeak`.
+            unsafe { ffi::objc_destroyWeak(self.location.get()) };
+        }
+    }
+}
+
+#[cfg(windows)]
+mod com {
+    //! Minimal bindings to COM's `IUnknown` interface.
+
+    use core::ffi::c_void;
+
+    /// The start of every COM vtable.
+    #[repr(C)]
+    struct IUnknownVtbl {
+        _query_interface: unsafe extern "system" fn(
+            this: *mut c_void,
+            iid: *const c_void,
+            object: *mut *mut c_void,
+        ) -> i32,
+        add_ref: unsafe extern "system" fn(this: *mut c_void) -> u32,
+        release: unsafe extern "system" fn(this: *mut c_void) -> u32,
+    }
+
+    /// Get the vtable of a COM object.
+    ///
+    /// # Safety
+    ///
+    /// `object` must point to a valid COM object.
+    unsafe fn vtable<'a>(object: *mut c_void) -> &'a IUnknownVtbl {
+        // SAFETY: The first field of every COM object is a pointer to its vtable.
+        unsafe { &**object.cast::<*const IUnknownVtbl>() }
+    }
+
+    /// Call `IUnknown::AddRef` on a COM object.
+    ///
+    /// # Safety
+    ///
+    /// `object` must point to a valid COM object.
+    pub(super) unsafe fn add_ref(object: *mut c_void) {
+        // SAFETY: The caller guarantees that `object` is a valid COM object.
+        unsafe { (vtable(object).add_ref)(object) };
+    }
+
+    /// Call `IUnknown::Release` on a COM object.
+    ///
+    /// # Safety
+    ///
+    /// `object` must point to a valid COM object with a reference held by the caller.
+    pub(super) unsafe fn release(object: *mut c_void) {
+        // SAFETY: The caller guarantees that `object` is a valid COM object.
+        unsafe { (vtable(object).release)(object) };
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    //! Bindings to the parts of `user32` we need.
+
+    /// Index of the window's instance handle.
+    const GWLP_HINSTANCE: i32 = -6;
+
+    #[link(name = "user32")]
+    extern "system" {
+        #[cfg(target_pointer_width = "64")]
+        fn GetWindowLongPtrW(hwnd: isize, index: i32) -> isize;
+
+        #[cfg(target_pointer_width = "32")]
+        fn GetWindowLongW(hwnd: isize, index: i32) -> i32;
+
+        fn IsWindow(hwnd: isize) -> i32;
+    }
+
+    /// Tell whether `hwnd` identifies an existing window.
+    pub(super) fn is_window(hwnd: isize) -> bool {
+        // SAFETY: `IsWindow` accepts any value.
+        unsafe { IsWindow(hwnd) != 0 }
+    }
+
+    /// Get the `HINSTANCE` of a window, or zero if it has none.
+    ///
+    /// # Safety
+    ///
+    /// `hwnd` must be a window handle.
+    pub(super) unsafe fn window_instance(hwnd: isize) -> isize {
+        // `GetWindowLongPtrW` is a macro for `GetWindowLongW` on 32-bit Windows.
+        #[cfg(target_pointer_width = "64")]
+        // SAFETY: The caller guarantees that `hwnd` is a window handle.
+        let instance = unsafe { GetWindowLongPtrW(hwnd, GWLP_HINSTANCE) };
+
+        #[cfg(target_pointer_width = "32")]
+        // SAFETY: The caller guarantees that `hwnd` is a window handle.
+        let instance = unsafe { GetWindowLongW(hwnd, GWLP_HINSTANCE) } as isize;
+
+        instance
+    }
+}
+
+#[cfg(target_env = "ohos")]
+mod ohos {
+    //! Bindings to the OpenHarmony native window API.
+
+    use core::ffi::c_void;
+
+    #[link(name = "native_window")]
+    extern "C" {
+        /// Increment the reference count of a native object.
+        pub(super) fn OH_NativeWindow_NativeObjectReference(obj: *mut c_void) -> i32;
+
+        /// Decrement the reference count of a native object.
+        pub(super) fn OH_NativeWindow_NativeObjectUnreference(obj: *mut c_void) -> i32;
+    }
+}
+
+#[cfg(feature = "leak-check")]
+mod leak_check {
+    //! Counter for window references held by this crate.
+
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Number of references currently held.
+    static OUTSTANDING: AtomicUsize = AtomicUsize::new(0);
+
+    /// Record that a reference was acquired.
+    pub(super) fn acquired() {
+        OUTSTANDING.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record that a reference was released.
+    pub(super) fn released() {
+        OUTSTANDING.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Get the number of references currently held.
+    pub(super) fn outstanding() -> usize {
+        OUTSTANDING.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(all(debug_assertions, feature = "double-drop-check"))]
+mod poison {
+    //! Detection of handles that are dropped twice.
+
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::collections::HashSet;
+    use std::sync::{Mutex, MutexGuard};
+
+    /// The next handle ID to hand out.
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+    /// ID's of the handles that are currently alive.
+    static LIVE: Mutex<Option<HashSet<usize>>> = Mutex::new(None);
+
+    /// Lock the set of live handles.
+    fn live() -> MutexGuard<'static, Option<HashSet<usize>>> {
+        LIVE.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Register a new handle, returning its ID.
+    pub(super) fn register() -> usize {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        live().get_or_insert_with(HashSet::new).insert(id);
+        id
+    }
+
+    /// Unregister a handle that is going away.
+    ///
+    /// Panics if the handle was already unregistered, which means it was copied bitwise and
+    /// dropped twice.
+    pub(super) fn unregister(id: usize) {
+        let removed = live().as_mut().map_or(false, |live| live.remove(&id));
+        assert!(
+            removed,
+            "`OwnedWindowHandle` was dropped twice, was it copied using `ptr::read`?"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a handle to an Xlib window that goes through `inc_refcount`.
+    fn xlib(window: core::ffi::c_ulong) -> OwnedWindowHandle {
+        // SAFETY: Xlib windows are just numeric ID's.
+        let handle = unsafe {
+            WindowHandle::borrow_raw(raw_window_handle::XlibWindowHandle::new(window).into())
+        };
+        OwnedWindowHandle::from_window_handle(handle).unwrap()
+    }
+
+    /// A window that can be shared between threads on every platform.
+    struct XlibWindow(core::ffi::c_ulong);
+
+    impl HasWindowHandle for XlibWindow {
+        fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+            // SAFETY: Xlib windows are just numeric ID's.
+            Ok(unsafe {
+                WindowHandle::borrow_raw(raw_window_handle::XlibWindowHandle::new(self.0).into())
+            })
+        }
+    }
+
+    #[test]
+    fn into_raw_rejects_unowned() {
+        let window = Arc::new(XlibWindow(1));
+        let shared = OwnedWindowHandle::borrowing(window).unwrap();
+        assert!(shared.into_raw().is_err());
+
+        // SAFETY: Xlib windows are just numeric ID's.
+        let assumed = unsafe {
+            OwnedWindowHandle::assume_direct(WindowHandle::borrow_raw(
+                raw_window_handle::XlibWindowHandle::new(1).into(),
+            ))
+        };
+        let assumed = assumed.into_raw().unwrap_err();
+        assert_eq!(assumed.as_raw(), xlib(1).as_raw());
+    }
+
+    #[test]
+    #[cfg(not(target_os = "haiku"))]
+    fn from_raw_rejects_unsupported() {
+        let raw = raw_window_handle::HaikuWindowHandle::new(ptr::NonNull::dangling()).into();
+
+        // SAFETY: The handle is rejected before it is used.
+        let err = unsafe { OwnedWindowHandle::from_raw(raw) }.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn new_x11_rejects_mismatched_pair() {
+        // SAFETY: X11 handles are just numeric ID's.
+        let (xlib_window, xcb_display) = unsafe {
+            (
+                WindowHandle::borrow_raw(raw_window_handle::XlibWindowHandle::new(1).into()),
+                raw_window_handle::DisplayHandle::borrow_raw(
+                    raw_window_handle::XcbDisplayHandle::new(None, 0).into(),
+                ),
             )
+        };
+
+        let err = OwnedWindowHandle::new_x11(xlib_window, xcb_display).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PlatformMismatch);
+        assert_eq!(err, Error(Repr::PlatformMismatch { expected: "xlib" }));
+    }
+
+    #[test]
+    #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+    fn appkit_off_main_thread() {
+        std::thread::spawn(|| {
+            let raw = raw_window_handle::AppKitWindowHandle::new(ptr::NonNull::dangling()).into();
+
+            // SAFETY: The view isn't messaged off the main thread.
+            let err = OwnedWindowHandle::new(unsafe { WindowHandle::borrow_raw(raw) }).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::NotMainThread);
+
+            // Dropping the handle leaks the reference instead of panicking.
+            // SAFETY: The view isn't messaged off the main thread.
+            drop(unsafe { OwnedWindowHandle::from_raw(raw) }.unwrap());
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn error_clone() {
+        let errors = [
+            Repr::Raw(HandleError::Unavailable),
+            Repr::PlatformMismatch { expected: "x11" },
+            Repr::NoWindow,
+            Repr::NoDocument,
+            Repr::CanvasNotFound(1),
+            Repr::RetainFailed(0x1000),
+            Repr::WaylandNotEnabled,
+            Repr::WaylandNotRust,
+            Repr::NotMainThread,
+            Repr::InvalidSelector,
+            Repr::VariantChanged,
+            Repr::InvalidPointer,
+            Repr::WaylandWrongInterface,
+            Repr::WaylandDestroyed,
+            Repr::InvalidHandle,
+            Repr::UnsupportedKind(RawKind::Xlib),
+        ];
+
+        for repr in errors {
+            let err = Error(repr);
+            let clone = err.clone();
+            assert_eq!(clone, err);
+            assert_eq!(clone.kind(), err.kind());
+            assert_eq!(clone.to_string(), err.to_string());
         }
+    }
 
-        // Default case: platform this version of the code doesn't anticipate.
-        _ => return Err(HandleError::NotSupported.into()),
-    };
+    #[test]
+    fn error_eq_platform_mismatch() {
+        let x11 = Error(Repr::PlatformMismatch { expected: "x11" });
+        assert_eq!(x11, Error(Repr::PlatformMismatch { expected: "x11" }));
+        assert_ne!(x11, Error(Repr::PlatformMismatch { expected: "apple" }));
+        assert_ne!(x11, Error(Repr::UnsupportedKind(RawKind::Xlib)));
+    }
 
-    // SAFETY: See above comments, this is always a valid handle.
-    Ok(Impl::Direct(unsafe { WindowHandle::borrow_raw(raw) }))
-}
+    #[test]
+    #[cfg(all(debug_assertions, feature = "double-drop-check"))]
+    #[should_panic(expected = "dropped twice")]
+    fn double_drop_panics() {
+        let handle = xlib(1);
 
-/// Decrement reference count of the underlying handle.
-///
-/// # Safety
-///
-/// `window` must have been created via [`inc_refcount`].
-unsafe fn dec_refcount(window: WindowHandle<'static>) -> Result<(), Error> {
-    match window.as_raw() {
-        RawWindowHandle::Xlib(_) => {
-            // We did nothing with the window above, so no need to do anything
-            // here either.
+        // SAFETY: Not safe at all, this is what's being tested. Xlib windows are plain
+        // numbers, so releasing one twice doesn't do anything bad.
+        let copy = unsafe { ptr::read(&handle) };
+
+        drop(handle);
+        drop(copy);
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn accessors_reject_wrong_platform() {
+        let xlib = OwnedWindowHandle::new_fake_xlib(1);
+        let xcb = OwnedWindowHandle::new_fake_xcb(core::num::NonZeroU32::new(1).unwrap());
+        let win32 = OwnedWindowHandle::new_fake_win32(core::num::NonZeroIsize::new(1).unwrap());
+
+        #[cfg(all(
+            unix,
+            not(any(
+                target_os = "redox",
+                target_family = "wasm",
+                target_os = "android",
+                target_vendor = "apple"
+            ))
+        ))]
+        {
+            assert!(xlib.as_xlib().is_some());
+            assert!(xlib.as_xcb().is_none());
+            assert!(xcb.as_xcb().is_some());
+            assert!(xcb.as_xlib().is_none());
+            assert!(win32.as_xlib().is_none());
+            assert!(win32.as_xcb().is_none());
         }
 
-        RawWindowHandle::Xcb(_) => {
-            // We did nothing with the window above, so no need to do anything
-            // here either.
+        #[cfg(windows)]
+        {
+            assert!(win32.as_win32().is_some());
+            assert!(xlib.as_win32().is_none());
+            assert!(xcb.as_win32().is_none());
         }
 
-        RawWindowHandle::Win32(_) => {
-            // We did nothing with the window above, so no need to do anything
-            // here either.
+        #[cfg(target_os = "macos")]
+        for handle in [&xlib, &xcb, &win32] {
+            assert!(handle.as_appkit().is_none());
         }
 
-        RawWindowHandle::Wayland(_) => unreachable!("inc_refcount never creates this variant"),
+        drop((xlib, xcb, win32));
+    }
 
-        RawWindowHandle::Drm(_) => {
-            // We did nothing with the window above, so no need to do anything
-            // here either.
-        }
+    #[test]
+    #[cfg(feature = "std")]
+    fn error_source() {
+        use std::error::Error as _;
 
-        #[cfg(not(target_os = "android"))]
-        RawWindowHandle::AndroidNdk(_) => {
-            return Err(Error(Repr::PlatformMismatch {
-                expected: "android",
-            }))
+        let err = Error::from(HandleError::Unavailable);
+        let source = err.source().unwrap();
+        assert_eq!(source.to_string(), HandleError::Unavailable.to_string());
+
+        assert!(Error(Repr::NoWindow).source().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn eq_same_window() {
+        let handle = OwnedWindowHandle::new_fake_xlib(1);
+        assert_eq!(handle.clone(), handle);
+        assert_eq!(OwnedWindowHandle::new_fake_xlib(1), handle);
+
+        assert_ne!(OwnedWindowHandle::new_fake_xlib(2), handle);
+        assert_ne!(
+            OwnedWindowHandle::new_fake_xcb(core::num::NonZeroU32::new(1).unwrap()),
+            handle
+        );
+        assert_ne!(
+            OwnedWindowHandle::new_fake_win32(core::num::NonZeroIsize::new(1).unwrap()),
+            handle
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "test-util"))]
+    fn hash_matches_eq() {
+        use std::collections::HashMap;
+
+        let handle = OwnedWindowHandle::new_fake_xlib(1);
+        let mut map = HashMap::new();
+        map.insert(handle.clone(), "first");
+        map.insert(handle, "second");
+        assert_eq!(map.len(), 1);
+        assert_eq!(map[&OwnedWindowHandle::new_fake_xlib(1)], "second");
+
+        map.insert(OwnedWindowHandle::new_fake_xlib(2), "other");
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn platform_per_variant() {
+        use core::num::{NonZeroIsize, NonZeroU32};
+
+        assert_eq!(
+            OwnedWindowHandle::new_fake_xlib(1).platform(),
+            Platform::Xlib
+        );
+        assert_eq!(
+            OwnedWindowHandle::new_fake_xcb(NonZeroU32::new(1).unwrap()).platform(),
+            Platform::Xcb
+        );
+        assert_eq!(
+            OwnedWindowHandle::new_fake_win32(NonZeroIsize::new(1).unwrap()).platform(),
+            Platform::Win32
+        );
+
+        // Every other platform needs a real window, so just copy the handles.
+        let dangling = ptr::NonNull::dangling();
+        let handles: [(RawWindowHandle, Platform); 7] = [
+            (
+                raw_window_handle::DrmWindowHandle::new(1).into(),
+                Platform::Drm,
+            ),
+            (
+                raw_window_handle::GbmWindowHandle::new(dangling).into(),
+                Platform::Gbm,
+            ),
+            (
+                raw_window_handle::WaylandWindowHandle::new(dangling).into(),
+                Platform::Wayland,
+            ),
+            (
+                raw_window_handle::AppKitWindowHandle::new(dangling).into(),
+                Platform::AppKit,
+            ),
+            (
+                raw_window_handle::UiKitWindowHandle::new(dangling).into(),
+                Platform::UiKit,
+            ),
+            (
+                raw_window_handle::AndroidNdkWindowHandle::new(dangling).into(),
+                Platform::AndroidNdk,
+            ),
+            (
+                raw_window_handle::WebWindowHandle::new(1).into(),
+                Platform::Web,
+            ),
+        ];
+        for (raw, platform) in handles {
+            // SAFETY: The handle is never dereferenced.
+            let handle = unsafe { OwnedWindowHandle::assume_direct(WindowHandle::borrow_raw(raw)) };
+            assert_eq!(handle.platform(), platform);
         }
+    }
 
-        #[cfg(target_os = "android")]
-        RawWindowHandle::AndroidNdk(android) => {
-            // Use ANativeWindow_acquire to bump the reference count.
-            // SAFETY: `android` is a valid pointer to an `ANativeWindow`.
-            unsafe { ndk_sys::ANativeWindow_release(android.a_native_window.as_ptr().cast()) };
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn debug_contains_xlib_window() {
+        let debug = format!("{:?}", OwnedWindowHandle::new_fake_xlib(0x1234));
+        assert!(debug.contains("Xlib"), "{}", debug);
+        assert!(debug.contains("window: 4660"), "{}", debug);
+    }
+
+    /// A window whose handle is unavailable for the first few attempts.
+    #[cfg(any(feature = "std", feature = "async"))]
+    struct Flaky {
+        failures: core::cell::Cell<usize>,
+        attempts: core::cell::Cell<usize>,
+    }
+
+    #[cfg(any(feature = "std", feature = "async"))]
+    impl Flaky {
+        fn new(failures: usize) -> Self {
+            Self {
+                failures: core::cell::Cell::new(failures),
+                attempts: core::cell::Cell::new(0),
+            }
         }
+    }
 
-        #[cfg(not(target_vendor = "apple"))]
-        RawWindowHandle::AppKit(_) | RawWindowHandle::UiKit(_) => {
-            return Err(Error(Repr::PlatformMismatch { expected: "apple" }))
+    #[cfg(any(feature = "std", feature = "async"))]
+    impl HasWindowHandle for Flaky {
+        fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+            self.attempts.set(self.attempts.get() + 1);
+            match self.failures.get() {
+                0 => {
+                    // SAFETY: Xlib windows are just numeric ID's.
+                    Ok(unsafe {
+                        WindowHandle::borrow_raw(raw_window_handle::XlibWindowHandle::new(1).into())
+                    })
+                }
+                failures => {
+                    self.failures.set(failures - 1);
+                    Err(HandleError::Unavailable)
+                }
+            }
         }
+    }
 
-        #[cfg(target_vendor = "apple")]
-        RawWindowHandle::AppKit(appkit) => {
-            use objc2::runtime::NSObject;
+    #[test]
+    #[cfg(feature = "std")]
+    fn new_with_retry_retries_unavailable() {
+        let backoff = std::time::Duration::from_millis(1);
 
-            // Use the "release" message to release the object.
-            // SAFETY: We know this is a valid `NsView`.
-            let view: *mut NSObject = appkit.ns_view.as_ptr().cast();
-            let _: () = unsafe { objc2::msg_send![view, release] };
+        let flaky = Flaky::new(2);
+        let handle = OwnedWindowHandle::new_with_retry(&flaky, 3, backoff).unwrap();
+        assert_eq!(handle.as_raw(), xlib(1).as_raw());
+        assert_eq!(flaky.attempts.get(), 3);
+
+        // Give up once the attempts are used up.
+        let flaky = Flaky::new(2);
+        let err = OwnedWindowHandle::new_with_retry(&flaky, 2, backoff).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unavailable);
+        assert_eq!(flaky.attempts.get(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn io_error_kind() {
+        let cases = [
+            (
+                Error::from(HandleError::NotSupported),
+                std::io::ErrorKind::Unsupported,
+            ),
+            (
+                Error::from(HandleError::Unavailable),
+                std::io::ErrorKind::NotFound,
+            ),
+            (Error(Repr::NotMainThread), std::io::ErrorKind::Other),
+        ];
+
+        for (err, kind) in cases {
+            let message = err.to_string();
+            let io = std::io::Error::from(err);
+            assert_eq!(io.kind(), kind);
+            assert_eq!(io.to_string(), message);
         }
+    }
 
-        #[cfg(target_vendor = "apple")]
-        RawWindowHandle::UiKit(uikit) => {
-            use objc2::runtime::NSObject;
+    #[test]
+    fn weak_upgrade_fails_once_strong_is_dropped() {
+        let handle = OwnedWindowHandle::owning(XlibWindow(1)).unwrap();
+        let weak = handle.downgrade();
+        assert_eq!(weak.upgrade().unwrap(), handle);
 
-            // Use the "release" message to release the object.
-            // SAFETY: We know this is a valid `UiView`.
-            let view: *mut NSObject = uikit.ui_view.as_ptr().cast();
-            let _: () = unsafe { objc2::msg_send![view, release] };
+        drop(handle);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    fn weak_gbm_is_untracked() {
+        let raw = raw_window_handle::GbmWindowHandle::new(ptr::NonNull::dangling()).into();
+
+        // SAFETY: GBM surfaces are only copied, never dereferenced.
+        let handle = OwnedWindowHandle::new(unsafe { WindowHandle::borrow_raw(raw) }).unwrap();
+
+        // The surface might be gone by the time the weak handle is upgraded.
+        assert!(handle.downgrade().upgrade().is_none());
+    }
+
+    #[test]
+    fn xlib_xcb_roundtrip() {
+        let xlib = OwnedWindowHandle::from_xlib(0x1234, 0x56);
+
+        let xcb = xlib.to_xcb().unwrap().unwrap();
+        match xcb.as_raw() {
+            RawWindowHandle::Xcb(xcb) => {
+                assert_eq!(xcb.window.get(), 0x1234);
+                assert_eq!(xcb.visual_id.map(|id| id.get()), Some(0x56));
+            }
+            raw => panic!("expected an XCB handle, got {:?}", raw),
         }
 
-        RawWindowHandle::Web(_) => unreachable!("inc_refcount never constructs this variant"),
+        let back = xcb.to_xlib().unwrap().unwrap();
+        assert_eq!(back.as_raw(), xlib.as_raw());
+        assert_eq!(back, xlib);
+    }
 
-        #[cfg(not(target_family = "wasm"))]
-        RawWindowHandle::WebCanvas(_) | RawWindowHandle::WebOffscreenCanvas(_) => {
-            return Err(Error(Repr::PlatformMismatch { expected: "wasm" }))
+    #[test]
+    fn to_xcb_drops_xlib_display() {
+        // SAFETY: X11 handles are just numeric ID's.
+        let (window, display) = unsafe {
+            (
+                WindowHandle::borrow_raw(raw_window_handle::XlibWindowHandle::new(1).into()),
+                raw_window_handle::DisplayHandle::borrow_raw(
+                    raw_window_handle::XlibDisplayHandle::new(None, 0).into(),
+                ),
+            )
+        };
+        let xlib = OwnedWindowHandle::new_x11(window, display).unwrap();
+
+        assert!(xlib.to_xlib().unwrap().unwrap().x11_display().is_some());
+        assert!(xlib.to_xcb().unwrap().unwrap().x11_display().is_none());
+    }
+
+    #[test]
+    #[cfg(all(target_pointer_width = "64", not(windows)))]
+    fn to_xcb_rejects_wide_xlib_window() {
+        let xlib = OwnedWindowHandle::from_xlib(u64::from(u32::MAX) + 1, 0);
+        assert!(xlib.to_xcb().unwrap().is_none());
+    }
+
+    #[test]
+    fn x11_conversion_rejects_other_platforms() {
+        let win32 = OwnedWindowHandle::from_win32(core::num::NonZeroIsize::new(1).unwrap(), None);
+        assert!(win32.to_xcb().unwrap().is_none());
+        assert!(win32.to_xlib().unwrap().is_none());
+    }
+
+    #[test]
+    fn raw_kind_is_distinct() {
+        use core::num::{NonZeroIsize, NonZeroU32};
+        use raw_window_handle::*;
+
+        let dangling = ptr::NonNull::dangling();
+        let handles: [RawWindowHandle; 16] = [
+            XlibWindowHandle::new(1).into(),
+            XcbWindowHandle::new(NonZeroU32::new(1).unwrap()).into(),
+            WaylandWindowHandle::new(dangling).into(),
+            DrmWindowHandle::new(1).into(),
+            GbmWindowHandle::new(dangling).into(),
+            Win32WindowHandle::new(NonZeroIsize::new(1).unwrap()).into(),
+            WinRtWindowHandle::new(dangling).into(),
+            AppKitWindowHandle::new(dangling).into(),
+            UiKitWindowHandle::new(dangling).into(),
+            AndroidNdkWindowHandle::new(dangling).into(),
+            OhosNdkWindowHandle::new(dangling).into(),
+            HaikuWindowHandle::new(dangling).into(),
+            OrbitalWindowHandle::new(dangling).into(),
+            WebWindowHandle::new(1).into(),
+            WebCanvasWindowHandle::new(1).into(),
+            WebOffscreenCanvasWindowHandle::new(1).into(),
+        ];
+
+        let kinds = handles.map(|raw| RawKind::of(&raw));
+        for (i, a) in kinds.iter().enumerate() {
+            assert_ne!(*a, RawKind::Other);
+            for b in &kinds[i + 1..] {
+                assert_ne!(a, b);
+            }
         }
+    }
 
-        #[cfg(target_family = "wasm")]
-        RawWindowHandle::WebCanvas(web) => {
-            use wasm_bindgen::convert::FromWasmAbi;
+    /// A window that returns a fixed result from `window_handle`.
+    struct Mock(Result<RawWindowHandle, HandleError>);
 
-            // We created a new object here. Drop it.
-            // SAFETY: This is a valid, owned object as constructed above.
-            let canvas = unsafe { web_sys::HtmlCanvasElement::from_abi(web.obj as _) };
-            drop(canvas);
+    impl HasWindowHandle for Mock {
+        fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+            match &self.0 {
+                // SAFETY: Mocks only hold plain values or handles that are rejected unused.
+                Ok(raw) => Ok(unsafe { WindowHandle::borrow_raw(*raw) }),
+                Err(HandleError::NotSupported) => Err(HandleError::NotSupported),
+                Err(_) => Err(HandleError::Unavailable),
+            }
         }
+    }
 
-        #[cfg(target_family = "wasm")]
-        RawWindowHandle::WebOffscreenCanvas(web) => {
-            use wasm_bindgen::convert::FromWasmAbi;
+    #[test]
+    fn try_new_skips_unsupported() {
+        let xlib = raw_window_handle::XlibWindowHandle::new(1).into();
+        let handle = OwnedWindowHandle::try_new(&Mock(Ok(xlib))).unwrap();
+        assert_eq!(handle.unwrap().as_raw(), xlib);
 
-            // We created a new object here. Drop it.
-            // SAFETY: This is a valid, owned object as constructed above.
-            let canvas = unsafe { web_sys::OffscreenCanvas::from_abi(web.obj as _) };
-            drop(canvas);
+        let unsupported = Mock(Err(HandleError::NotSupported));
+        assert!(OwnedWindowHandle::try_new(&unsupported).unwrap().is_none());
+
+        // WinRT windows only exist on Windows, so they are rejected before being used.
+        #[cfg(not(windows))]
+        {
+            let winrt = raw_window_handle::WinRtWindowHandle::new(ptr::NonNull::dangling());
+            let mismatch = Mock(Ok(winrt.into()));
+            assert!(OwnedWindowHandle::try_new(&mismatch).unwrap().is_none());
         }
 
-        // Default case: platform this version of the code doesn't anticipate.
-        _ => return Err(HandleError::NotSupported.into()),
+        let unavailable = Mock(Err(HandleError::Unavailable));
+        let err = OwnedWindowHandle::try_new(&unavailable).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unavailable);
     }
 
-    Ok(())
-}
+    #[test]
+    #[cfg(windows)]
+    fn win32_hinstance_present_or_absent() {
+        use core::num::NonZeroIsize;
 
-/// Possible error codes.
-#[allow(dead_code)]
-#[derive(Debug)]
-enum Repr {
-    /// Underlying [`raw-window-handle`] error.
-    Raw(HandleError),
+        let hwnd = NonZeroIsize::new(0x1234).unwrap();
+        let hinstance = NonZeroIsize::new(0x5678).unwrap();
 
-    /// This is the wrong platform to use this function.
-    PlatformMismatch {
-        /// The platform we expected.
-        expected: &'static str,
-    },
+        let win32 = OwnedWindowHandle::from_win32(hwnd, Some(hinstance));
+        assert_eq!(win32.win32_hinstance(), Some(0x5678));
 
-    /// Crucial elements are missing on web.
-    MissingWebElements,
+        let win32 = OwnedWindowHandle::from_win32(hwnd, None);
+        assert_eq!(win32.win32_hinstance(), None);
 
-    /// Canvas not found with the specific ID.
-    CanvasNotFound(u32),
+        assert_eq!(xlib(1).win32_hinstance(), None);
+    }
 
-    /// Retain failed.
-    RetainFailed,
+    #[test]
+    fn handle_matches_window_handle() {
+        let handle = xlib(1);
+        let raw = handle.handle().unwrap().as_raw();
+        assert_eq!(raw, handle.as_raw());
+        assert_eq!(raw, handle.window_handle().unwrap().as_raw());
+    }
 
-    /// Wayland is not enabled.
-    WaylandNotEnabled,
+    #[test]
+    fn size_hint_matches_size_of() {
+        assert_eq!(
+            OwnedWindowHandle::size_hint(),
+            mem::size_of::<OwnedWindowHandle>()
+        );
+        assert_eq!(
+            OwnedWindowHandle::align_hint(),
+            mem::align_of::<OwnedWindowHandle>()
+        );
 
-    /// The resulting Wayland handle was not created by Rust's `wayland-backend`.
-    WaylandNotRust,
-}
+        // Usable in constants, for statically sized storage.
+        const SIZE: usize = OwnedWindowHandle::size_hint();
+        let storage = [mem::MaybeUninit::<u8>::uninit(); SIZE];
+        assert_eq!(storage.len(), mem::size_of::<OwnedWindowHandle>());
+    }
 
-#[cfg(any(
-    not(feature = "wayland"),
-    not(all(
+    #[test]
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    fn drm_plane_must_be_nonzero() {
+        let drm = |plane| {
+            // SAFETY: DRM planes are just numeric ID's.
+            let handle = unsafe {
+                WindowHandle::borrow_raw(raw_window_handle::DrmWindowHandle::new(plane).into())
+            };
+            OwnedWindowHandle::new(handle)
+        };
+
+        let err = drm(0).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidHandle);
+
+        let handle = drm(1).unwrap();
+        assert_eq!(handle.platform(), Platform::Drm);
+        assert_eq!(handle.identity(), 1);
+    }
+
+    #[test]
+    fn with_raw_reads_hwnd() {
+        let hwnd = core::num::NonZeroIsize::new(0x1234).unwrap();
+        let win32 = OwnedWindowHandle::from_win32(hwnd, None);
+
+        let read = win32.with_raw(|raw| match raw {
+            RawWindowHandle::Win32(win32) => Some(win32.hwnd),
+            _ => None,
+        });
+        assert_eq!(read, Some(hwnd));
+    }
+
+    #[test]
+    fn retain_failed_display() {
+        let err = Error(Repr::RetainFailed(0x1000));
+        assert_eq!(err.kind(), ErrorKind::RetainFailed);
+        assert_eq!(err.to_string(), "failed to retain window handle at 0x1000");
+    }
+
+    #[test]
+    #[cfg(all(
         unix,
         not(any(
             target_os = "redox",
@@ -424,88 +4268,207 @@ enum Repr {
             target_os = "android",
             target_vendor = "apple"
         ))
-    ))
-))]
-mod wayland {
-    /// Wayland handle.
-    pub(super) type WaylandHandle = core::convert::Infallible;
+    ))]
+    fn xcb_visual_id() {
+        let window = core::num::NonZeroU32::new(1).unwrap();
+        let visual = core::num::NonZeroU32::new(0x21).unwrap();
 
-    /// Create a new `WaylandHandle` from the raw wayland handle.
-    pub(super) unsafe fn clone_handle(
-        _handle: raw_window_handle::WaylandWindowHandle,
-    ) -> Result<WaylandHandle, crate::Error> {
-        Err(crate::Error(crate::Repr::WaylandNotEnabled))
+        let xcb = OwnedWindowHandle::from_xcb(window, None);
+        assert_eq!(xcb.xcb_visual_id(), None);
+
+        let xcb = xcb.with_xcb_visual_id(visual).unwrap();
+        assert_eq!(xcb.xcb_visual_id(), Some(0x21));
+        assert_eq!(OwnedWindowHandle::from_xcb(window, Some(visual)), xcb);
+
+        let xlib = OwnedWindowHandle::from_xlib(1, 0x21);
+        assert_eq!(xlib.xcb_visual_id(), None);
+        let err = xlib.with_xcb_visual_id(visual).unwrap_err();
+        assert_eq!(err, Error(Repr::PlatformMismatch { expected: "xcb" }));
     }
 
-    /// Convert the `WaylandHandle` into a window handle.
-    pub(super) fn as_ptr(
-        handle: &WaylandHandle,
-    ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
-        match *handle {}
+    /// Poll a future that is expected to finish without waiting on anything.
+    #[cfg(feature = "async")]
+    fn block_on<F: core::future::Future>(fut: F) -> F::Output {
+        use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+        fn raw() -> RawWaker {
+            RawWaker::new(ptr::null(), &VTABLE)
+        }
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(|_| raw(), |_| {}, |_| {}, |_| {});
+
+        // SAFETY: The waker doesn't do anything, so its data pointer is never used.
+        let waker = unsafe { Waker::from_raw(raw()) };
+        let mut fut = fut;
+
+        // SAFETY: `fut` is never moved after being pinned.
+        let fut = unsafe { core::pin::Pin::new_unchecked(&mut fut) };
+        match fut.poll(&mut Context::from_waker(&waker)) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future did not finish"),
+        }
     }
-}
 
-#[cfg(all(
-    feature = "wayland",
-    unix,
-    not(any(
-        target_os = "redox",
-        target_family = "wasm",
-        target_os = "android",
-        target_vendor = "apple"
-    ))
-))]
-mod wayland {
-    use wayland_backend::sys::client as wc;
-    use wayland_client::Proxy;
+    #[test]
+    #[cfg(feature = "async")]
+    fn new_async_retries_unavailable() {
+        let backoff = core::time::Duration::from_millis(1);
+        let sleeps = core::cell::Cell::new(0);
+        let sleep = |duration| {
+            assert_eq!(duration, backoff);
+            sleeps.set(sleeps.get() + 1);
+            core::future::ready(())
+        };
 
-    /// Tracked Wayland handle.
-    #[derive(Clone)]
-    pub(super) struct WaylandHandle {
-        /// The Wayland object ID.
-        id: wc::ObjectId,
+        let flaky = Flaky::new(2);
+        let handle = block_on(OwnedWindowHandle::new_async(&flaky, 3, backoff, sleep)).unwrap();
+        assert_eq!(handle.as_raw(), xlib(1).as_raw());
+        assert_eq!(flaky.attempts.get(), 3);
+        assert_eq!(sleeps.get(), 2);
+
+        // Give up once the attempts are used up.
+        let flaky = Flaky::new(2);
+        let err = block_on(OwnedWindowHandle::new_async(&flaky, 2, backoff, sleep)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unavailable);
+        assert_eq!(flaky.attempts.get(), 2);
+        assert_eq!(sleeps.get(), 3);
     }
 
-    /// Get a `WaylandHandle` from a `*mut wl_proxy`.
-    pub(super) unsafe fn clone_handle(
-        handle: raw_window_handle::WaylandWindowHandle,
-    ) -> Result<WaylandHandle, crate::Error> {
-        let ptr = handle.surface;
+    #[test]
+    fn from_values_match_inputs() {
+        let mut xlib = raw_window_handle::XlibWindowHandle::new(0x1234);
+        xlib.visual_id = 0x56;
+        assert_eq!(
+            OwnedWindowHandle::from_xlib(0x1234, 0x56).as_raw(),
+            xlib.into()
+        );
 
-        // Create the `ObjectId` from the `wl_surface` pointer.
-        let id = wc::ObjectId::from_ptr(
-            wayland_client::protocol::wl_surface::WlSurface::interface(),
-            ptr.as_ptr().cast(),
-        )
-        .map_err(|_| crate::Error(crate::Repr::WaylandNotRust))?;
+        let window = core::num::NonZeroU32::new(0x1234).unwrap();
+        let visual = core::num::NonZeroU32::new(0x56);
+        let mut xcb = raw_window_handle::XcbWindowHandle::new(window);
+        xcb.visual_id = visual;
+        assert_eq!(
+            OwnedWindowHandle::from_xcb(window, visual).as_raw(),
+            xcb.into()
+        );
 
-        // Get the `Backend` so we can call `get_data()`.
-        let display_ptr = id.display_ptr();
-        if display_ptr.is_null() {
-            return Err(crate::Error(crate::Repr::WaylandNotRust));
+        let hwnd = core::num::NonZeroIsize::new(0x1234).unwrap();
+        let hinstance = core::num::NonZeroIsize::new(0x56);
+        for hinstance in [hinstance, None] {
+            let mut win32 = raw_window_handle::Win32WindowHandle::new(hwnd);
+            win32.hinstance = hinstance;
+            assert_eq!(
+                OwnedWindowHandle::from_win32(hwnd, hinstance).as_raw(),
+                win32.into()
+            );
         }
-        let backend = unsafe { wc::Backend::from_foreign_display(display_ptr) };
+    }
 
-        /* Ensure the object is owned by Rust's wayland-backend. */
-        if backend.get_data(id.clone()).is_err() {
-            return Err(crate::Error(crate::Repr::WaylandNotRust));
+    #[test]
+    #[cfg(not(windows))]
+    fn try_clone_many_into_keeps_pool_on_failure() {
+        let good = xlib(1);
+        let mut pool = vec![xlib(2)];
+
+        good.try_clone_many_into(&mut pool, 3).unwrap();
+        assert_eq!(pool.len(), 4);
+        assert!(pool[1..].iter().all(|clone| *clone == good));
+
+        // WinRT windows can't be cloned outside of Windows. Releasing it would fail too, so
+        // it is never dropped.
+        let winrt = raw_window_handle::WinRtWindowHandle::new(ptr::NonNull::dangling());
+        // SAFETY: The window is never dereferenced, since WinRT isn't supported here.
+        let bad = mem::ManuallyDrop::new(OwnedWindowHandle::from_impl(Impl::Direct(unsafe {
+            WindowHandle::borrow_raw(winrt.into())
+        })));
+
+        let err = bad.try_clone_many_into(&mut pool, 2).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PlatformMismatch);
+
+        // The handles that were already in the pool are kept.
+        assert_eq!(pool.len(), 4);
+        assert_eq!(pool[0], xlib(2));
+        assert!(pool[1..].iter().all(|clone| *clone == good));
+
+        good.try_clone_many_into(&mut pool, 0).unwrap();
+        assert_eq!(pool.len(), 4);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "haiku"))]
+    fn unsupported_kind_names_the_kind() {
+        let raw = raw_window_handle::HaikuWindowHandle::new(ptr::NonNull::dangling()).into();
+
+        // SAFETY: The handle is rejected before it is used.
+        let err = unsafe { OwnedWindowHandle::from_raw(raw) }.unwrap_err();
+        assert_eq!(err, Error(Repr::UnsupportedKind(RawKind::Haiku)));
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert_eq!(
+            err.to_string(),
+            "Haiku window handles are not supported by this build"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn identity_of_fakes() {
+        let xlib = OwnedWindowHandle::new_fake_xlib(0x1234);
+        assert_eq!(xlib.identity(), 0x1234);
+        assert_eq!(xlib.clone().identity(), xlib.identity());
+        assert_ne!(
+            OwnedWindowHandle::new_fake_xlib(0x1235).identity(),
+            xlib.identity()
+        );
+
+        let xcb = OwnedWindowHandle::new_fake_xcb(core::num::NonZeroU32::new(0x1234).unwrap());
+        assert_eq!(xcb.clone().identity(), xcb.identity());
+        let other = OwnedWindowHandle::new_fake_xcb(core::num::NonZeroU32::new(0x1235).unwrap());
+        assert_ne!(other.identity(), xcb.identity());
+
+        let win32 =
+            OwnedWindowHandle::new_fake_win32(core::num::NonZeroIsize::new(0x1234).unwrap());
+        assert_eq!(win32.clone().identity(), win32.identity());
+        let other =
+            OwnedWindowHandle::new_fake_win32(core::num::NonZeroIsize::new(0x1235).unwrap());
+        assert_ne!(other.identity(), win32.identity());
+    }
+
+    #[test]
+    #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+    fn is_kind_of_rejects_other_classes() {
+        extern "C" {
+            fn objc_getClass(name: *const core::ffi::c_char) -> *mut core::ffi::c_void;
         }
 
-        Ok(WaylandHandle { id })
+        // SAFETY: The name is nul-terminated.
+        let class = unsafe { objc_getClass(b"NSObject\0".as_ptr().cast()) };
+        let class = ptr::NonNull::new(class).unwrap();
+
+        // SAFETY: Classes are Objective-C objects too, and are never deallocated.
+        unsafe {
+            assert!(!apple::is_kind_of(class, b"NSView\0"));
+            assert!(!apple::is_kind_of(class, b"UIView\0"));
+            assert!(!apple::is_kind_of(class, b"NoSuchClass\0"));
+        }
     }
 
-    /// Convert the `WaylandHandle` into a window handle.
-    pub(super) fn as_ptr(
-        handle: &WaylandHandle,
-    ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
-        match core::ptr::NonNull::new(handle.id.as_ptr()) {
-            None => Err(raw_window_handle::HandleError::Unavailable),
-            Some(non_null) => {
-                let raw = raw_window_handle::WaylandWindowHandle::new(non_null.cast()).into();
+    #[test]
+    fn escape_ident_matches_css_escape() {
+        let cases = [
+            ("data-leptos-canvas", "data-leptos-canvas"),
+            ("1a", "\\31 a"),
+            ("-1", "-\\31 "),
+            ("a1", "a1"),
+            ("-", "\\-"),
+            ("--", "--"),
+            ("a\0b", "a\u{fffd}b"),
+            ("\x01", "\\1 "),
+            ("data:canvas.id", "data\\:canvas\\.id"),
+            ("é", "é"),
+            ("", ""),
+        ];
 
-                // SAFETY: The proxy is being kept alive, so we know it's valid.
-                Ok(unsafe { raw_window_handle::WindowHandle::borrow_raw(raw) })
-            }
+        for (ident, escaped) in cases {
+            assert_eq!(escape_ident(ident), escaped, "escaping {:?}", ident);
         }
     }
 }
@@ -0,0 +1,40 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Extensions for AppKit views.
+//!
+//! ```no_run
+//! use owned_window_handle::platform::macos::AppKitWindowHandleExt;
+//! use owned_window_handle::OwnedWindowHandle;
+//!
+//! fn print_view(handle: &OwnedWindowHandle) {
+//!     if let Some(view) = handle.ns_view() {
+//!         println!("NSView: {:p}", view);
+//!     }
+//! }
+//! ```
+
+use crate::OwnedWindowHandle;
+
+use core::ffi::c_void;
+use core::ptr::NonNull;
+
+/// Additional methods for AppKit views.
+pub trait AppKitWindowHandleExt {
+    /// Get the `NSView` pointer, if this is an AppKit view.
+    ///
+    /// The view is retained for as long as this handle is alive.
+    fn ns_view(&self) -> Option<NonNull<c_void>>;
+}
+
+impl AppKitWindowHandleExt for OwnedWindowHandle {
+    #[inline]
+    fn ns_view(&self) -> Option<NonNull<c_void>> {
+        self.as_appkit().map(|appkit| appkit.ns_view)
+    }
+}
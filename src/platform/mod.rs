@@ -0,0 +1,39 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Platform-specific extensions to [`OwnedWindowHandle`](crate::OwnedWindowHandle).
+//!
+//! Each module contains an extension trait that is implemented for
+//! [`OwnedWindowHandle`](crate::OwnedWindowHandle), and is only available on the platforms it
+//! applies to.
+
+#[cfg(target_os = "macos")]
+pub mod macos;
+#[cfg(all(
+    feature = "wayland",
+    unix,
+    not(any(
+        target_os = "redox",
+        target_family = "wasm",
+        target_os = "android",
+        target_vendor = "apple"
+    ))
+))]
+pub mod wayland;
+#[cfg(windows)]
+pub mod windows;
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "redox",
+        target_family = "wasm",
+        target_os = "android",
+        target_vendor = "apple"
+    ))
+))]
+pub mod x11;
@@ -0,0 +1,48 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Extensions for Wayland surfaces.
+//!
+//! ```no_run
+//! use owned_window_handle::platform::wayland::WaylandWindowHandleExt;
+//! use owned_window_handle::OwnedWindowHandle;
+//!
+//! fn egl_pointers(handle: &OwnedWindowHandle) {
+//!     if let (Some(surface), Some(display)) = (handle.wl_surface(), handle.wl_display()) {
+//!         println!("wl_surface: {:p}, wl_display: {:p}", surface, display);
+//!     }
+//! }
+//! ```
+
+use crate::OwnedWindowHandle;
+
+use core::ffi::c_void;
+use core::ptr::NonNull;
+
+/// Additional methods for Wayland surfaces.
+pub trait WaylandWindowHandleExt {
+    /// Get the `wl_surface` pointer, if this is a Wayland surface.
+    fn wl_surface(&self) -> Option<NonNull<c_void>>;
+
+    /// Get the `wl_display` pointer the surface belongs to, if this is a Wayland surface.
+    ///
+    /// See [`OwnedWindowHandle::wayland_display_ptr`].
+    fn wl_display(&self) -> Option<NonNull<c_void>>;
+}
+
+impl WaylandWindowHandleExt for OwnedWindowHandle {
+    #[inline]
+    fn wl_surface(&self) -> Option<NonNull<c_void>> {
+        self.as_wayland().map(|wayland| wayland.surface)
+    }
+
+    #[inline]
+    fn wl_display(&self) -> Option<NonNull<c_void>> {
+        self.wayland_display_ptr()
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Extensions for Win32 windows.
+//!
+//! ```
+//! use owned_window_handle::platform::windows::Win32WindowHandleExt;
+//! use owned_window_handle::OwnedWindowHandle;
+//!
+//! let handle = OwnedWindowHandle::from_hwnd(1).unwrap();
+//! assert_eq!(handle.hwnd(), Some(1));
+//! assert_eq!(handle.hinstance(), None);
+//! ```
+
+use crate::OwnedWindowHandle;
+
+use core::num::NonZeroIsize;
+
+/// Additional methods for Win32 windows.
+pub trait Win32WindowHandleExt: Sized {
+    /// Get the `HWND` of this window, if this is a Win32 window.
+    fn hwnd(&self) -> Option<isize>;
+
+    /// Get the `HINSTANCE` of this window, if this is a Win32 window that has one.
+    ///
+    /// See [`OwnedWindowHandle::win32_hinstance`].
+    fn hinstance(&self) -> Option<isize>;
+
+    /// Create a handle to a Win32 window from its `HWND`.
+    ///
+    /// Returns `None` if `hwnd` is zero.
+    fn from_hwnd(hwnd: isize) -> Option<Self>;
+}
+
+impl Win32WindowHandleExt for OwnedWindowHandle {
+    #[inline]
+    fn hwnd(&self) -> Option<isize> {
+        self.as_win32().map(|win32| win32.hwnd.get())
+    }
+
+    #[inline]
+    fn hinstance(&self) -> Option<isize> {
+        self.win32_hinstance()
+    }
+
+    #[inline]
+    fn from_hwnd(hwnd: isize) -> Option<Self> {
+        NonZeroIsize::new(hwnd).map(|hwnd| OwnedWindowHandle::from_win32(hwnd, None))
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Extensions for X11 windows.
+//!
+//! ```
+//! use owned_window_handle::platform::x11::X11WindowHandleExt;
+//! use owned_window_handle::OwnedWindowHandle;
+//!
+//! let handle = OwnedWindowHandle::from_xlib_window(1);
+//! assert_eq!(handle.x11_window(), Some(1));
+//! ```
+
+use crate::OwnedWindowHandle;
+
+use core::ffi::c_ulong;
+use raw_window_handle::RawWindowHandle;
+
+/// Additional methods for X11 windows.
+pub trait X11WindowHandleExt: Sized {
+    /// Get the ID of this window, if this is an Xlib or XCB window.
+    fn x11_window(&self) -> Option<u32>;
+
+    /// Get the ID of the visual of this window, if this is an Xlib or XCB window that has
+    /// one.
+    fn x11_visual_id(&self) -> Option<u32>;
+
+    /// Create a handle to an Xlib window from its ID.
+    fn from_xlib_window(window: c_ulong) -> Self;
+}
+
+impl X11WindowHandleExt for OwnedWindowHandle {
+    #[inline]
+    fn x11_window(&self) -> Option<u32> {
+        match self.as_raw() {
+            // X11 resource ID's are 29 bits wide, so this never truncates real windows.
+            RawWindowHandle::Xlib(xlib) => Some(xlib.window as u32),
+            RawWindowHandle::Xcb(xcb) => Some(xcb.window.get()),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn x11_visual_id(&self) -> Option<u32> {
+        match self.as_raw() {
+            RawWindowHandle::Xlib(xlib) if xlib.visual_id != 0 => Some(xlib.visual_id as u32),
+            RawWindowHandle::Xcb(xcb) => xcb.visual_id.map(|id| id.get()),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn from_xlib_window(window: c_ulong) -> Self {
+        OwnedWindowHandle::from_xlib(window, 0)
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Compatibility with `raw-window-handle` v0.5.
+
+use crate::{Error, OwnedWindowHandle, Repr};
+
+use core::num::{NonZeroIsize, NonZeroU32};
+use core::ptr::NonNull;
+use raw_window_handle::{
+    AndroidNdkWindowHandle, AppKitWindowHandle, DrmWindowHandle, GbmWindowHandle,
+    HaikuWindowHandle, HandleError, OrbitalWindowHandle, RawWindowHandle, UiKitWindowHandle,
+    WaylandWindowHandle, WebWindowHandle, Win32WindowHandle, WinRtWindowHandle, WindowHandle,
+    XcbWindowHandle, XlibWindowHandle,
+};
+use raw_window_handle_05 as rwh_05;
+
+impl OwnedWindowHandle {
+    /// Create a new [`OwnedWindowHandle`] from a `raw-window-handle` v0.5 window.
+    ///
+    /// The v0.5 handle is converted to its v0.6 equivalent and then owned as usual. Handles
+    /// that have no v0.6 equivalent, or that contain null pointers, are rejected.
+    pub fn from_rwh_05(handle: impl rwh_05::HasRawWindowHandle) -> Result<Self, Error> {
+        let raw = convert(handle.raw_window_handle())?;
+
+        // SAFETY: `HasRawWindowHandle` guarantees that the handle is valid.
+        let handle = unsafe { WindowHandle::borrow_raw(raw) };
+        Self::from_window_handle(handle)
+    }
+}
+
+/// Convert a v0.5 window handle into a v0.6 window handle.
+fn convert(raw: rwh_05::RawWindowHandle) -> Result<RawWindowHandle, Error> {
+    let raw = match raw {
+        rwh_05::RawWindowHandle::Xlib(xlib) => {
+            let mut handle = XlibWindowHandle::new(xlib.window);
+            handle.visual_id = xlib.visual_id;
+            handle.into()
+        }
+
+        rwh_05::RawWindowHandle::Xcb(xcb) => {
+            let mut handle = XcbWindowHandle::new(non_zero_u32(xcb.window)?);
+            handle.visual_id = NonZeroU32::new(xcb.visual_id);
+            handle.into()
+        }
+
+        rwh_05::RawWindowHandle::Wayland(wayland) => {
+            WaylandWindowHandle::new(non_null(wayland.surface)?).into()
+        }
+
+        rwh_05::RawWindowHandle::Drm(drm) => DrmWindowHandle::new(drm.plane).into(),
+
+        rwh_05::RawWindowHandle::Gbm(gbm) => {
+            GbmWindowHandle::new(non_null(gbm.gbm_surface)?).into()
+        }
+
+        rwh_05::RawWindowHandle::Win32(win32) => {
+            let hwnd = NonZeroIsize::new(win32.hwnd as isize).ok_or(Error(Repr::InvalidPointer))?;
+            let mut handle = Win32WindowHandle::new(hwnd);
+            handle.hinstance = NonZeroIsize::new(win32.hinstance as isize);
+            handle.into()
+        }
+
+        rwh_05::RawWindowHandle::WinRt(winrt) => {
+            WinRtWindowHandle::new(non_null(winrt.core_window)?).into()
+        }
+
+        rwh_05::RawWindowHandle::AppKit(appkit) => {
+            AppKitWindowHandle::new(non_null(appkit.ns_view)?).into()
+        }
+
+        rwh_05::RawWindowHandle::UiKit(uikit) => {
+            let mut handle = UiKitWindowHandle::new(non_null(uikit.ui_view)?);
+            handle.ui_view_controller = NonNull::new(uikit.ui_view_controller);
+            handle.into()
+        }
+
+        rwh_05::RawWindowHandle::AndroidNdk(android) => {
+            AndroidNdkWindowHandle::new(non_null(android.a_native_window)?).into()
+        }
+
+        rwh_05::RawWindowHandle::Haiku(haiku) => {
+            let mut handle = HaikuWindowHandle::new(non_null(haiku.b_window)?);
+            handle.b_direct_window = NonNull::new(haiku.b_direct_window);
+            handle.into()
+        }
+
+        rwh_05::RawWindowHandle::Orbital(orbital) => {
+            OrbitalWindowHandle::new(non_null(orbital.window)?).into()
+        }
+
+        rwh_05::RawWindowHandle::Web(web) => WebWindowHandle::new(web.id).into(),
+
+        // Default case: variant that has no v0.6 equivalent we know of.
+        _ => return Err(HandleError::NotSupported.into()),
+    };
+
+    Ok(raw)
+}
+
+/// Convert a window pointer that must not be null.
+fn non_null<T>(ptr: *mut T) -> Result<NonNull<T>, Error> {
+    NonNull::new(ptr).ok_or(Error(Repr::InvalidPointer))
+}
+
+/// Convert a window ID that must not be zero.
+fn non_zero_u32(id: u32) -> Result<NonZeroU32, Error> {
+    NonZeroU32::new(id).ok_or(Error(Repr::InvalidHandle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    #[test]
+    fn xlib() {
+        let mut xlib = rwh_05::XlibWindowHandle::empty();
+        xlib.window = 1;
+        xlib.visual_id = 2;
+
+        let raw = convert(rwh_05::RawWindowHandle::Xlib(xlib)).unwrap();
+        let mut expected = XlibWindowHandle::new(1);
+        expected.visual_id = 2;
+        assert_eq!(raw, RawWindowHandle::Xlib(expected));
+    }
+
+    #[test]
+    fn xcb() {
+        let mut xcb = rwh_05::XcbWindowHandle::empty();
+        xcb.window = 1;
+
+        let raw = convert(rwh_05::RawWindowHandle::Xcb(xcb)).unwrap();
+        let expected = XcbWindowHandle::new(NonZeroU32::new(1).unwrap());
+        assert_eq!(raw, RawWindowHandle::Xcb(expected));
+    }
+
+    #[test]
+    fn xcb_zero_window() {
+        let xcb = rwh_05::XcbWindowHandle::empty();
+        let err = convert(rwh_05::RawWindowHandle::Xcb(xcb)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidHandle);
+    }
+
+    #[test]
+    fn wayland_null_surface() {
+        let wayland = rwh_05::WaylandWindowHandle::empty();
+        let err = convert(rwh_05::RawWindowHandle::Wayland(wayland)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidPointer);
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! `serde` support for numeric window handles.
+//!
+//! Only handles that are plain values (Xlib, XCB, Win32 and DRM) can be serialized. Pointers
+//! are meaningless outside of the process that created them, so they are rejected.
+
+use crate::OwnedWindowHandle;
+
+use core::ffi::c_ulong;
+use core::num::{NonZeroIsize, NonZeroU32};
+use raw_window_handle::{
+    DrmWindowHandle, RawWindowHandle, Win32WindowHandle, WindowHandle, XcbWindowHandle,
+    XlibWindowHandle,
+};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serialized form of a window handle.
+#[derive(Serialize, Deserialize)]
+enum SerializedHandle {
+    /// An Xlib window.
+    Xlib { window: c_ulong, visual_id: c_ulong },
+
+    /// An XCB window.
+    Xcb {
+        window: NonZeroU32,
+        visual_id: Option<NonZeroU32>,
+    },
+
+    /// A Win32 window.
+    Win32 {
+        hwnd: NonZeroIsize,
+        hinstance: Option<NonZeroIsize>,
+    },
+
+    /// A DRM plane.
+    Drm { plane: u32 },
+}
+
+impl Serialize for OwnedWindowHandle {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let handle = match self.as_raw() {
+            RawWindowHandle::Xlib(xlib) => SerializedHandle::Xlib {
+                window: xlib.window,
+                visual_id: xlib.visual_id,
+            },
+            RawWindowHandle::Xcb(xcb) => SerializedHandle::Xcb {
+                window: xcb.window,
+                visual_id: xcb.visual_id,
+            },
+            RawWindowHandle::Win32(win32) => SerializedHandle::Win32 {
+                hwnd: win32.hwnd,
+                hinstance: win32.hinstance,
+            },
+            RawWindowHandle::Drm(drm) => SerializedHandle::Drm { plane: drm.plane },
+            _ => {
+                return Err(ser::Error::custom(
+                    "only numeric window handles can be serialized",
+                ))
+            }
+        };
+
+        handle.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for OwnedWindowHandle {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = match SerializedHandle::deserialize(deserializer)? {
+            SerializedHandle::Xlib { window, visual_id } => {
+                let mut xlib = XlibWindowHandle::new(window);
+                xlib.visual_id = visual_id;
+                RawWindowHandle::Xlib(xlib)
+            }
+            SerializedHandle::Xcb { window, visual_id } => {
+                let mut xcb = XcbWindowHandle::new(window);
+                xcb.visual_id = visual_id;
+                RawWindowHandle::Xcb(xcb)
+            }
+            SerializedHandle::Win32 { hwnd, hinstance } => {
+                let mut win32 = Win32WindowHandle::new(hwnd);
+                win32.hinstance = hinstance;
+                RawWindowHandle::Win32(win32)
+            }
+            SerializedHandle::Drm { plane } => RawWindowHandle::Drm(DrmWindowHandle::new(plane)),
+        };
+
+        // SAFETY: All of the above handles are plain values that don't point to anything.
+        let handle = unsafe { WindowHandle::borrow_raw(raw) };
+        OwnedWindowHandle::from_window_handle(handle).map_err(de::Error::custom)
+    }
+}
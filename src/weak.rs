@@ -0,0 +1,184 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Weak references to window handles.
+
+use crate::{wayland, Impl, OwnedWindowHandle, ThreadMarker};
+
+use core::any::Any;
+use core::fmt;
+use core::marker::PhantomData;
+use raw_window_handle::{RawWindowHandle, WindowHandle};
+use std::sync::Weak;
+
+/// A weak reference to a window handle.
+///
+/// This does not keep the window alive. Use [`WeakWindowHandle::upgrade`] to get an
+/// [`OwnedWindowHandle`] back, if the window still exists.
+///
+/// How this works depends on the platform:
+///
+/// - Handles that are plain values can always be upgraded. These are Xlib, XCB, Win32, DRM
+///   and web handles. Upgraded X11 handles don't carry their display connection.
+/// - Handles created using [`OwnedWindowHandle::assume_direct`] can always be upgraded.
+/// - Wayland handles can be upgraded as long as a clone of the [`OwnedWindowHandle`] they
+///   were created from is alive, and the surface hasn't been destroyed. The weak handle
+///   doesn't keep the `wayland-backend` connection alive.
+/// - AppKit and UIKit views are tracked by the Objective-C runtime, and can be upgraded as
+///   long as the view hasn't been deallocated. Upgrading fails off the main thread.
+/// - Handles created using [`OwnedWindowHandle::borrowing`] can be upgraded as long as the
+///   shared window is alive.
+/// - Every other window, including GBM surfaces, Haiku and Orbital windows, has no way to
+///   tell whether it is still alive, so the weak handle can never be upgraded.
+pub struct WeakWindowHandle {
+    /// Underlying implementation.
+    imp: WeakImpl,
+
+    /// Marker restricting which threads this handle can be used on.
+    _marker: PhantomData<ThreadMarker>,
+}
+
+/// Underlying implementation.
+enum WeakImpl {
+    /// A window that doesn't need a reference to stay valid.
+    Value(WindowHandle<'static>),
+
+    /// Wayland surface.
+    Wayland(wayland::WeakWaylandHandle),
+
+    /// Apple view tracked by the Objective-C runtime.
+    #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+    Apple {
+        /// The original handle, used as a template when upgrading.
+        raw: RawWindowHandle,
+
+        /// The weak reference to the view.
+        weak: crate::apple::WeakRef,
+    },
+
+    /// Window kept alive by a shared pointer.
+    Shared {
+        /// The window handle borrowed from the window.
+        handle: WindowHandle<'static>,
+
+        /// The window itself.
+        window: Weak<dyn Any + Send + Sync>,
+    },
+
+    /// A window that the caller promised can be copied without any refcounting.
+    Assumed(WindowHandle<'static>),
+
+    /// A window that can't be tracked weakly.
+    Untracked,
+}
+
+// SAFETY: Same reasoning as for `OwnedWindowHandle`.
+#[cfg(not(any(windows, target_vendor = "apple", target_family = "wasm")))]
+unsafe impl Send for WeakWindowHandle {}
+
+// SAFETY: Same reasoning as for `OwnedWindowHandle`.
+#[cfg(not(any(windows, target_vendor = "apple", target_family = "wasm")))]
+unsafe impl Sync for WeakWindowHandle {}
+
+impl fmt::Debug for WeakWindowHandle {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakWindowHandle").finish_non_exhaustive()
+    }
+}
+
+impl WeakWindowHandle {
+    /// Create a weak reference to an owned window handle.
+    pub(crate) fn new(handle: &OwnedWindowHandle) -> Self {
+        let imp = match &handle.imp {
+            Impl::Direct(handle) | Impl::X11 { window: handle, .. } => match handle.as_raw() {
+                #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+                RawWindowHandle::AppKit(appkit) => apple(handle.as_raw(), appkit.ns_view),
+
+                #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+                RawWindowHandle::UiKit(uikit) => apple(handle.as_raw(), uikit.ui_view),
+
+                RawWindowHandle::Xlib(_)
+                | RawWindowHandle::Xcb(_)
+                | RawWindowHandle::Win32(_)
+                | RawWindowHandle::Drm(_)
+                | RawWindowHandle::Web(_) => WeakImpl::Value(*handle),
+
+                // Pointers might dangle once the window is gone.
+                _ => WeakImpl::Untracked,
+            },
+
+            Impl::Wayland(wayland) => WeakImpl::Wayland(wayland::downgrade(wayland)),
+
+            Impl::Assumed(handle) => WeakImpl::Assumed(*handle),
+
+            Impl::Shared { handle, window } => WeakImpl::Shared {
+                handle: *handle,
+                window: std::sync::Arc::downgrade(window),
+            },
+        };
+
+        Self {
+            imp,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get an owned window handle back, if the window is still alive.
+    pub fn upgrade(&self) -> Option<OwnedWindowHandle> {
+        let imp = match &self.imp {
+            WeakImpl::Value(handle) => return OwnedWindowHandle::from_window_handle(*handle).ok(),
+
+            WeakImpl::Wayland(wayland) => Impl::Wayland(wayland::upgrade(wayland)?),
+
+            #[cfg(all(target_vendor = "apple", feature = "appkit"))]
+            WeakImpl::Apple { raw, weak } => {
+                // Views must only be retained on the main thread.
+                if !crate::apple::is_main_thread() {
+                    return None;
+                }
+
+                // This hands us a retained view, which is what `Impl::Direct` expects.
+                let object = weak.load()?;
+                let raw = match *raw {
+                    RawWindowHandle::AppKit(mut appkit) => {
+                        appkit.ns_view = object;
+                        RawWindowHandle::AppKit(appkit)
+                    }
+                    RawWindowHandle::UiKit(mut uikit) => {
+                        uikit.ui_view = object;
+                        RawWindowHandle::UiKit(uikit)
+                    }
+                    _ => unreachable!(),
+                };
+
+                // SAFETY: We hold a reference to the view, so it is valid.
+                Impl::Direct(unsafe { WindowHandle::borrow_raw(raw) })
+            }
+
+            WeakImpl::Shared { handle, window } => Impl::Shared {
+                handle: *handle,
+                window: window.upgrade()?,
+            },
+
+            WeakImpl::Assumed(handle) => Impl::Assumed(*handle),
+
+            WeakImpl::Untracked => return None,
+        };
+
+        Some(OwnedWindowHandle::from_impl(imp))
+    }
+}
+
+/// Create a weak reference to an Apple view.
+#[cfg(all(target_vendor = "apple", feature = "appkit"))]
+fn apple(raw: RawWindowHandle, view: core::ptr::NonNull<core::ffi::c_void>) -> WeakImpl {
+    // SAFETY: We hold a reference to the view, so it is valid.
+    let weak = unsafe { crate::apple::WeakRef::new(view) };
+    WeakImpl::Apple { raw, weak }
+}
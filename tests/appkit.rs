@@ -0,0 +1,70 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Tests for AppKit windows.
+//!
+//! Views can only be messaged from the main thread, and the default test harness runs
+//! every test on its own thread. So, this runs without the harness.
+
+#[cfg(all(target_os = "macos", feature = "objc2"))]
+fn main() {
+    from_ns_window_off_main_thread();
+    from_ns_window_balances_retains();
+}
+
+#[cfg(not(all(target_os = "macos", feature = "objc2")))]
+fn main() {}
+
+#[cfg(all(target_os = "macos", feature = "objc2"))]
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {}
+
+#[cfg(all(target_os = "macos", feature = "objc2"))]
+fn from_ns_window_off_main_thread() {
+    std::thread::spawn(|| {
+        // SAFETY: The window isn't messaged off the main thread.
+        let err = unsafe {
+            owned_window_handle::OwnedWindowHandle::from_ns_window(std::ptr::NonNull::dangling())
+        }
+        .unwrap_err();
+        assert_eq!(err.kind(), owned_window_handle::ErrorKind::NotMainThread);
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(all(target_os = "macos", feature = "objc2"))]
+fn from_ns_window_balances_retains() {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use owned_window_handle::OwnedWindowHandle;
+
+    // SAFETY: This is the main thread, and every message is sent to a valid object.
+    unsafe {
+        let _app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+        let window: *mut AnyObject = msg_send![class!(NSWindow), alloc];
+        let window: *mut AnyObject = msg_send![window, init];
+        let view: *mut AnyObject = msg_send![window, contentView];
+        let retain_count = || -> usize { msg_send![view, retainCount] };
+        let before = retain_count();
+
+        let handle =
+            OwnedWindowHandle::from_ns_window(std::ptr::NonNull::new(window.cast()).unwrap())
+                .unwrap();
+        assert_eq!(handle.apple_retain_count(), Some(before + 1));
+        assert_eq!(retain_count(), before + 1);
+
+        let clone = handle.clone();
+        assert_eq!(retain_count(), before + 2);
+
+        drop((handle, clone));
+        assert_eq!(retain_count(), before);
+
+        let _: () = msg_send![window, release];
+    }
+}
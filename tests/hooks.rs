@@ -0,0 +1,98 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Tests for the global lifecycle hook.
+
+#![cfg(feature = "hooks")]
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use owned_window_handle::raw_window_handle::{WindowHandle, XlibWindowHandle};
+use owned_window_handle::{set_global_hook, HookEvent, OwnedWindowHandle, RawKind};
+
+/// The hook is global, so tests that replace it can't run in parallel.
+fn lock() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Register a hook that records every event.
+fn record() -> Arc<Mutex<Vec<HookEvent>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    set_global_hook({
+        let events = events.clone();
+        move |event| events.lock().unwrap().push(event)
+    });
+    events
+}
+
+/// Create a handle to an Xlib window.
+fn xlib(window: core::ffi::c_ulong) -> OwnedWindowHandle {
+    // SAFETY: Xlib windows are just numeric ID's.
+    let handle = unsafe { WindowHandle::borrow_raw(XlibWindowHandle::new(window).into()) };
+    OwnedWindowHandle::new(handle).unwrap()
+}
+
+#[test]
+fn event_sequence() {
+    let _lock = lock();
+    let events = record();
+
+    let handle = xlib(1);
+    let clone = handle.clone();
+    drop(handle);
+    let raw = clone.into_raw().unwrap();
+
+    // SAFETY: `raw` holds the reference handed over by `into_raw`.
+    let handle = unsafe { OwnedWindowHandle::from_raw(raw) }.unwrap();
+    drop(handle);
+
+    set_global_hook(|_| {});
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            HookEvent::Created(RawKind::Xlib),
+            HookEvent::Created(RawKind::Xlib),
+            HookEvent::Dropped(RawKind::Xlib),
+            HookEvent::Dropped(RawKind::Xlib),
+            HookEvent::Created(RawKind::Xlib),
+            HookEvent::Dropped(RawKind::Xlib),
+        ]
+    );
+}
+
+#[test]
+fn hook_can_reenter() {
+    let _lock = lock();
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    // The first event replaces the hook, and the new hook creates and drops a handle.
+    set_global_hook({
+        let events = events.clone();
+        move |event| {
+            events.lock().unwrap().push(event);
+
+            let events = events.clone();
+            set_global_hook(move |event| events.lock().unwrap().push(event));
+            drop(xlib(2));
+        }
+    });
+
+    drop(xlib(1));
+
+    set_global_hook(|_| {});
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            HookEvent::Created(RawKind::Xlib),
+            HookEvent::Created(RawKind::Xlib),
+            HookEvent::Dropped(RawKind::Xlib),
+            HookEvent::Dropped(RawKind::Xlib),
+        ]
+    );
+}
@@ -0,0 +1,243 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Tests that window references are balanced, using `outstanding_handles`.
+
+#![cfg(feature = "leak-check")]
+
+use std::sync::{Mutex, MutexGuard};
+
+use owned_window_handle::raw_window_handle::{
+    HandleError, HasWindowHandle, WindowHandle, XlibWindowHandle,
+};
+use owned_window_handle::{outstanding_handles, own_all, ErrorKind, OwnedWindowHandle};
+
+/// The reference counter is global, so tests that look at it can't run in parallel.
+fn lock() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Create a handle to an Xlib window.
+fn xlib(window: core::ffi::c_ulong) -> OwnedWindowHandle {
+    // SAFETY: Xlib windows are just numeric ID's.
+    let handle = unsafe { WindowHandle::borrow_raw(XlibWindowHandle::new(window).into()) };
+    OwnedWindowHandle::new(handle).unwrap()
+}
+
+/// A window that may fail to produce a handle.
+enum Window {
+    /// An Xlib window with this ID.
+    Good(core::ffi::c_ulong),
+
+    /// A window whose handle is unavailable.
+    Bad,
+}
+
+impl HasWindowHandle for Window {
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        match self {
+            Self::Good(window) => {
+                // SAFETY: Xlib windows are just numeric ID's.
+                Ok(unsafe { WindowHandle::borrow_raw(XlibWindowHandle::new(*window).into()) })
+            }
+            Self::Bad => Err(HandleError::Unavailable),
+        }
+    }
+}
+
+#[test]
+fn clone_acquires_reference() {
+    let _lock = lock();
+    let outstanding = outstanding_handles();
+
+    let handle = xlib(1);
+    assert_eq!(outstanding_handles(), outstanding + 1);
+
+    let clone = handle.clone();
+    assert_eq!(clone, handle);
+    assert_eq!(outstanding_handles(), outstanding + 2);
+
+    let mut other = xlib(2);
+    assert_eq!(outstanding_handles(), outstanding + 3);
+
+    // `clone_from` releases the window it replaces.
+    other.clone_from(&handle);
+    assert_eq!(other, handle);
+    assert_eq!(outstanding_handles(), outstanding + 3);
+
+    drop((handle, clone, other));
+    assert_eq!(outstanding_handles(), outstanding);
+}
+
+#[test]
+fn try_clone_into_reuses_same_window() {
+    let _lock = lock();
+    let outstanding = outstanding_handles();
+
+    let src = xlib(1);
+    let mut dst = xlib(1);
+    src.try_clone_into(&mut dst).unwrap();
+    assert_eq!(dst.as_raw(), src.as_raw());
+    assert_eq!(outstanding_handles(), outstanding + 2);
+
+    // A different window is released once the clone was acquired.
+    let mut other = xlib(2);
+    src.try_clone_into(&mut other).unwrap();
+    assert_eq!(other.as_raw(), src.as_raw());
+    assert_eq!(outstanding_handles(), outstanding + 3);
+
+    drop((src, dst, other));
+    assert_eq!(outstanding_handles(), outstanding);
+}
+
+#[test]
+fn new_batch_balances() {
+    let _lock = lock();
+    let outstanding = outstanding_handles();
+
+    // SAFETY: Xlib windows are just numeric ID's.
+    let window = unsafe { WindowHandle::borrow_raw(XlibWindowHandle::new(1).into()) };
+    let handles = OwnedWindowHandle::new_batch(&window, 4).unwrap();
+    assert_eq!(handles.len(), 4);
+    assert_eq!(outstanding_handles(), outstanding + 4);
+    for handle in &handles {
+        assert_eq!(handle.as_raw(), window.as_raw());
+    }
+
+    drop(handles);
+    assert_eq!(outstanding_handles(), outstanding);
+
+    assert!(OwnedWindowHandle::new_batch(&window, 0).unwrap().is_empty());
+    assert_eq!(outstanding_handles(), outstanding);
+}
+
+#[test]
+fn replace_releases_old_window() {
+    let _lock = lock();
+    let outstanding = outstanding_handles();
+
+    let mut handle = xlib(1);
+    // SAFETY: Xlib windows are just numeric ID's.
+    let other = unsafe { WindowHandle::borrow_raw(XlibWindowHandle::new(2).into()) };
+
+    handle.replace(&other).unwrap();
+    assert_eq!(handle.as_raw(), other.as_raw());
+    assert_eq!(outstanding_handles(), outstanding + 1);
+
+    drop(handle);
+    assert_eq!(outstanding_handles(), outstanding);
+}
+
+#[test]
+fn own_all_releases_on_failure() {
+    let _lock = lock();
+    let outstanding = outstanding_handles();
+
+    let windows = [Window::Good(1), Window::Good(2), Window::Good(3)];
+    let handles = own_all(&windows).unwrap();
+    assert_eq!(handles.len(), 3);
+    assert_eq!(outstanding_handles(), outstanding + 3);
+    drop(handles);
+    assert_eq!(outstanding_handles(), outstanding);
+
+    // The windows owned before the bad one are released again.
+    let windows = [
+        Window::Good(1),
+        Window::Good(2),
+        Window::Bad,
+        Window::Good(3),
+    ];
+    let err = own_all(&windows).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unavailable);
+    assert_eq!(outstanding_handles(), outstanding);
+}
+
+#[test]
+fn into_raw_keeps_reference() {
+    let _lock = lock();
+    let outstanding = outstanding_handles();
+
+    let handle = xlib(1);
+    let clones = handle.try_clone_array::<2>().unwrap();
+    assert_eq!(outstanding_handles(), outstanding + 3);
+
+    // The reference is handed over to the caller.
+    let raw = handle.into_raw().unwrap();
+    assert_eq!(outstanding_handles(), outstanding + 2);
+
+    // SAFETY: `raw` still holds the reference taken above.
+    let handle = unsafe { OwnedWindowHandle::from_raw(raw) }.unwrap();
+    assert_eq!(handle.as_raw(), raw);
+    assert_eq!(outstanding_handles(), outstanding + 3);
+
+    drop((handle, clones));
+    assert_eq!(outstanding_handles(), outstanding);
+}
+
+#[test]
+fn try_clone_array_balances() {
+    let _lock = lock();
+    let outstanding = outstanding_handles();
+
+    let handle = xlib(1);
+    let clones = handle.try_clone_array::<3>().unwrap();
+    assert_eq!(outstanding_handles(), outstanding + 4);
+    for clone in &clones {
+        assert_eq!(*clone, handle);
+        assert_eq!(clone.as_raw(), handle.as_raw());
+    }
+
+    drop(clones);
+    assert_eq!(outstanding_handles(), outstanding + 1);
+
+    drop(handle);
+    assert_eq!(outstanding_handles(), outstanding);
+}
+
+#[test]
+fn weak_upgrade_acquires_reference() {
+    let _lock = lock();
+    let outstanding = outstanding_handles();
+
+    let handle = xlib(1);
+    let weak = handle.try_clone_weak().unwrap();
+    assert_eq!(outstanding_handles(), outstanding + 1);
+
+    let upgraded = weak.upgrade().unwrap();
+    assert_eq!(upgraded, handle);
+    assert_eq!(outstanding_handles(), outstanding + 2);
+
+    drop((handle, upgraded));
+    assert_eq!(outstanding_handles(), outstanding);
+
+    // A shared window is gone once the last strong handle is dropped.
+    let handle = OwnedWindowHandle::owning(Window::Good(2)).unwrap();
+    let weak = handle.try_clone_weak().unwrap();
+    drop(handle);
+    assert!(weak.upgrade().is_none());
+    assert_eq!(outstanding_handles(), outstanding);
+}
+
+#[test]
+fn try_clone_many_into_balances() {
+    let _lock = lock();
+    let outstanding = outstanding_handles();
+
+    let handle = xlib(1);
+    let mut pool = vec![xlib(2)];
+    handle.try_clone_many_into(&mut pool, 3).unwrap();
+    assert_eq!(outstanding_handles(), outstanding + 5);
+
+    // Truncating the pool releases the clones.
+    pool.truncate(1);
+    assert_eq!(outstanding_handles(), outstanding + 2);
+
+    drop((handle, pool));
+    assert_eq!(outstanding_handles(), outstanding);
+}
@@ -0,0 +1,59 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Property tests for `roundtrip_raw`.
+
+#![cfg(feature = "test-util")]
+
+use core::ffi::c_ulong;
+use core::num::{NonZeroIsize, NonZeroU32};
+use core::ptr::NonNull;
+
+use owned_window_handle::raw_window_handle::{
+    RawWindowHandle, WaylandWindowHandle, Win32WindowHandle, XcbWindowHandle, XlibWindowHandle,
+};
+use owned_window_handle::{roundtrip_raw, ErrorKind};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn xlib(window: c_ulong, visual_id: c_ulong) {
+        let mut xlib = XlibWindowHandle::new(window);
+        xlib.visual_id = visual_id;
+
+        let raw = RawWindowHandle::Xlib(xlib);
+        prop_assert_eq!(roundtrip_raw(raw).unwrap(), raw);
+    }
+
+    #[test]
+    fn xcb(window in 1..=u32::MAX, visual_id: u32) {
+        let mut xcb = XcbWindowHandle::new(NonZeroU32::new(window).unwrap());
+        xcb.visual_id = NonZeroU32::new(visual_id);
+
+        let raw = RawWindowHandle::Xcb(xcb);
+        prop_assert_eq!(roundtrip_raw(raw).unwrap(), raw);
+    }
+
+    #[test]
+    fn win32(hwnd in 1..=isize::MAX, hinstance: isize) {
+        let mut win32 = Win32WindowHandle::new(NonZeroIsize::new(hwnd).unwrap());
+        win32.hinstance = NonZeroIsize::new(hinstance);
+
+        let raw = RawWindowHandle::Win32(win32);
+        prop_assert_eq!(roundtrip_raw(raw).unwrap(), raw);
+    }
+}
+
+#[test]
+fn rejects_pointers() {
+    let raw = RawWindowHandle::Wayland(WaylandWindowHandle::new(NonNull::dangling()));
+    assert_eq!(
+        roundtrip_raw(raw).unwrap_err().kind(),
+        ErrorKind::Unsupported
+    );
+}
@@ -0,0 +1,101 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Tests for the events emitted with the `tracing` feature.
+
+#![cfg(feature = "tracing")]
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use owned_window_handle::raw_window_handle::{WindowHandle, XlibWindowHandle};
+use owned_window_handle::OwnedWindowHandle;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// The fields of an event, formatted using `Debug`.
+#[derive(Default)]
+struct Fields(BTreeMap<&'static str, String>);
+
+/// A subscriber that records every event.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<Fields>>>);
+
+impl Capture {
+    /// Take the events recorded so far.
+    fn take(&self) -> Vec<Fields> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name(), format!("{:?}", value));
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(fields);
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+/// Get the messages of the recorded events, checking that every operation succeeded.
+fn messages(events: &[Fields]) -> Vec<&str> {
+    events
+        .iter()
+        .map(|Fields(fields)| {
+            assert_eq!(fields["platform"], "Xlib");
+            assert_eq!(fields["window"], "Some(1)");
+            assert_eq!(fields["ok"], "true");
+            fields["message"].as_str()
+        })
+        .collect()
+}
+
+#[test]
+fn create_clone_drop() {
+    let capture = Capture::default();
+
+    tracing::subscriber::with_default(capture.clone(), || {
+        // SAFETY: Xlib windows are just numeric ID's.
+        let window = unsafe { WindowHandle::borrow_raw(XlibWindowHandle::new(1).into()) };
+
+        let handle = OwnedWindowHandle::new(window).unwrap();
+        assert_eq!(messages(&capture.take()), ["acquired window reference"]);
+
+        let clone = handle.try_clone().unwrap();
+        assert_eq!(messages(&capture.take()), ["acquired window reference"]);
+
+        drop((handle, clone));
+        assert_eq!(
+            messages(&capture.take()),
+            ["released window reference", "released window reference"]
+        );
+    });
+}
@@ -0,0 +1,130 @@
+// Copyright (c) 2025 The Winit Publishers
+//
+// This software is release under one of the following licenses, at your option:
+//
+// - The MIT License
+// - The Zlib License
+// - The Apache License, Version 2.0
+
+//! Tests for web canvases.
+//!
+//! These need a browser, run them using `wasm-pack test --headless --firefox`.
+
+#![cfg(all(target_family = "wasm", feature = "web"))]
+
+use owned_window_handle::raw_window_handle::{WebWindowHandle, WindowHandle};
+use owned_window_handle::OwnedWindowHandle;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// A canvas in the document, removed again when dropped.
+struct Canvas(web_sys::HtmlCanvasElement);
+
+impl Canvas {
+    /// Add a canvas with the given `data-raw-handle` ID to the document.
+    fn new(id: u32) -> Self {
+        Self::with_attr("data-raw-handle", id)
+    }
+
+    /// Add a canvas whose `attr` attribute is set to the given ID to the document.
+    fn with_attr(attr: &str, id: u32) -> Self {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas: web_sys::HtmlCanvasElement =
+            document.create_element("canvas").unwrap().unchecked_into();
+        canvas.set_attribute(attr, &id.to_string()).unwrap();
+        document.body().unwrap().append_child(&canvas).unwrap();
+        Self(canvas)
+    }
+}
+
+impl Drop for Canvas {
+    fn drop(&mut self) {
+        self.0.remove();
+    }
+}
+
+/// Get a `Web` window handle for the canvas with the given ID.
+fn web(id: u32) -> WindowHandle<'static> {
+    // SAFETY: Web handles are just ID's.
+    unsafe { WindowHandle::borrow_raw(WebWindowHandle::new(id).into()) }
+}
+
+#[wasm_bindgen_test]
+fn same_window_by_id() {
+    let _canvas = Canvas::new(1);
+    let _other = Canvas::new(2);
+
+    let handle = OwnedWindowHandle::new(web(1)).unwrap();
+    assert!(handle.is_same_window(web(1)).unwrap());
+    assert!(!handle.is_same_window(web(2)).unwrap());
+    assert!(!handle.is_same_window(web(3)).unwrap());
+}
+
+/// Dropping a handle created from a `Web` ID must release the element it looked up.
+///
+/// A `WeakRef` would only be cleared once the garbage collector gets around to it, so this
+/// counts the live objects in the `wasm-bindgen` heap instead.
+#[wasm_bindgen_test]
+fn web_handle_releases_element() {
+    let _canvas = Canvas::new(1);
+
+    // The first lookup caches the document for good, so get that out of the way.
+    drop(OwnedWindowHandle::new(web(1)).unwrap());
+    let live = wasm_bindgen::externref_heap_live_count();
+
+    let handle = OwnedWindowHandle::new(web(1)).unwrap();
+    assert_eq!(wasm_bindgen::externref_heap_live_count(), live + 1);
+
+    let clone = handle.try_clone().unwrap();
+    assert_eq!(wasm_bindgen::externref_heap_live_count(), live + 2);
+
+    drop((handle, clone));
+    assert_eq!(wasm_bindgen::externref_heap_live_count(), live);
+}
+
+#[wasm_bindgen_test]
+fn many_handles_by_id() {
+    let canvases = (0..10).map(Canvas::new).collect::<Vec<_>>();
+
+    let handles = (0..100)
+        .map(|i| OwnedWindowHandle::new(web(i % 10)).unwrap())
+        .collect::<Vec<_>>();
+    for (i, handle) in handles.iter().enumerate() {
+        let id = i as u32 % 10;
+        assert!(handle.is_same_window(web(id)).unwrap());
+        assert!(!handle.is_same_window(web((id + 1) % 10)).unwrap());
+    }
+
+    // Replacing a canvas must not hand out the removed one.
+    drop(canvases);
+    assert!(OwnedWindowHandle::new(web(0)).is_err());
+
+    let _canvas = Canvas::new(0);
+    let handle = OwnedWindowHandle::new(web(0)).unwrap();
+    assert!(!handle.is_same_window(&handles[0]).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn new_web_with_attr() {
+    let canvas = Canvas::with_attr("data-leptos-canvas", 1);
+    let _other = Canvas::new(1);
+
+    let handle = OwnedWindowHandle::new_web_with_attr(1, "data-leptos-canvas").unwrap();
+    assert_eq!(handle.canvas_element(), Some(canvas.0.clone()));
+    assert!(OwnedWindowHandle::new_web_with_attr(2, "data-leptos-canvas").is_err());
+}
+
+#[wasm_bindgen_test]
+fn new_web_with_attr_escapes() {
+    // Unescaped, the colon and the dot would be read as a namespace and a class.
+    let canvas = Canvas::with_attr("data-app:canvas.main", 1);
+
+    let handle = OwnedWindowHandle::new_web_with_attr(1, "data-app:canvas.main").unwrap();
+    assert_eq!(handle.canvas_element(), Some(canvas.0.clone()));
+
+    // An empty attribute can't be escaped into a valid selector.
+    let err = OwnedWindowHandle::new_web_with_attr(1, "").unwrap_err();
+    assert_eq!(err.kind(), owned_window_handle::ErrorKind::InvalidSelector);
+}